@@ -1,3 +1,4 @@
+use crate::rng::Rng;
 use macroquad::prelude::Color;
 use std::fmt::Debug;
 
@@ -9,7 +10,7 @@ pub struct AnimationTime {
 }
 
 pub trait Animate {
-    fn animate(&self, data: &mut AnimationData, time: &AnimationTime);
+    fn animate(&self, data: &mut AnimationData, time: &AnimationTime, rng: &Rng);
 }
 
 impl Debug for dyn Animate {
@@ -23,4 +24,6 @@ pub struct AnimationData {
     pub radius: f32,
     pub vx: f32,
     pub vy: f32,
+    /// Colour when the window of the running animation opened, kept per particle and animation.
+    pub start_color: Option<Color>,
 }
@@ -1,14 +1,25 @@
 use super::animation::{Animate, AnimationData, AnimationTime};
-use macroquad::prelude::rand;
+use crate::rng::Rng;
+use macroquad::prelude::Color;
 use std::fmt::Debug;
 use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct AnimationHandler {
+    tracks: Vec<TrackState>,
+}
+
+/// Per particle state of an animation track.
+#[derive(Debug)]
+struct TrackState {
     animation_offset_ms: u32,
-    iteration: u32,
     animations: Rc<Vec<Box<dyn Animate>>>,
     duration_ms: u32,
+    play_mode: PlayMode,
+    loop_count: Option<u32>,
+    timing: AnimationTiming,
+    /// AnimationData::start_color of every animation.
+    start_colors: Vec<Option<Color>>,
 }
 
 pub enum StartAnimationAt {
@@ -17,11 +28,41 @@ pub enum StartAnimationAt {
     RangeMs(u32, u32),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayMode {
+    /// Starts over from 0 after every iteration.
+    Loop,
+    /// Plays every other iteration backwards.
+    PingPong,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationTiming {
+    /// Cycles over the elapsed time since the particle spawned.
+    Elapsed,
+    /**
+     * Plays once over the lifetime of the particle, duration_ms stands for the whole lifetime.
+     * E.g. with a duration of 1000 ms, until_ms: 500 is halfway through the life of the particle.
+     * Doesn't play on particles that live forever.
+     */
+    OverLifetime,
+}
+
+/// Animations sharing a duration, every track of a particle runs independently.
 #[derive(Debug)]
-pub struct AnimationOptions {
+pub struct AnimationTrack {
     pub animations: Rc<Vec<Box<dyn Animate>>>,
     pub duration_ms: u32,
     pub start_at: StartAnimationAt,
+    pub play_mode: PlayMode,
+    /// Iterations before the track stops, Some(1) plays it once. Repeats forever if None.
+    pub loop_count: Option<u32>,
+    pub timing: AnimationTiming,
+}
+
+#[derive(Debug)]
+pub struct AnimationOptions {
+    pub tracks: Vec<AnimationTrack>,
 }
 
 impl Debug for StartAnimationAt {
@@ -31,50 +72,98 @@ impl Debug for StartAnimationAt {
 }
 
 impl AnimationHandler {
-    pub fn new(options: &Option<AnimationOptions>) -> Option<Self> {
+    pub fn new(options: &Option<AnimationOptions>, rng: &Rng) -> Option<Self> {
         match options {
             Some(ah) => {
-                let animation_offset_ms = match ah.start_at {
-                    StartAnimationAt::Zero => 0,
-                    StartAnimationAt::Random => rand::gen_range(0, ah.duration_ms),
-                    StartAnimationAt::RangeMs(start, end) => rand::gen_range(start, end),
-                };
-                Some(AnimationHandler {
-                    iteration: 0,
-                    animation_offset_ms,
-                    animations: Rc::clone(&ah.animations),
-                    duration_ms: ah.duration_ms,
-                })
+                let tracks = ah
+                    .tracks
+                    .iter()
+                    .map(|track| {
+                        let animation_offset_ms = match track.start_at {
+                            StartAnimationAt::Zero => 0,
+                            StartAnimationAt::Random => rng.gen_range(0, track.duration_ms),
+                            StartAnimationAt::RangeMs(start, end) => rng.gen_range(start, end),
+                        };
+
+                        TrackState {
+                            animation_offset_ms,
+                            animations: Rc::clone(&track.animations),
+                            // Cycles are taken modulo the duration, which can't be 0.
+                            duration_ms: track.duration_ms.max(1),
+                            play_mode: track.play_mode,
+                            loop_count: track.loop_count,
+                            timing: track.timing,
+                            start_colors: vec![None; track.animations.len()],
+                        }
+                    })
+                    .collect();
+
+                Some(AnimationHandler { tracks })
             }
             None => None,
         }
     }
 
-    pub fn animate(&mut self, data: &mut AnimationData, elapsed_ms: u128) {
-        //let new_iteration = if elapsed_ms == 0 {
-        //0
-        //} else {
-        //self.duration_ms / elapsed_ms
-        //};
+    /// lifetime_ms is None for particles that live forever.
+    pub fn animate(
+        &mut self,
+        data: &mut AnimationData,
+        elapsed_ms: u128,
+        lifetime_ms: Option<u128>,
+        rng: &Rng,
+    ) {
+        for track in self.tracks.iter_mut() {
+            let cycle_ms = match (track.timing, lifetime_ms) {
+                (AnimationTiming::Elapsed, _) => match track.cycle_ms(elapsed_ms) {
+                    Some(cycle_ms) => cycle_ms,
+                    None => continue,
+                },
+                (AnimationTiming::OverLifetime, Some(lifetime_ms)) => {
+                    let fraction = elapsed_ms as f64 / lifetime_ms.max(1) as f64;
+                    let cycle_ms = (fraction * track.duration_ms as f64) as u32;
+                    cycle_ms.min(track.duration_ms.saturating_sub(1))
+                }
+                (AnimationTiming::OverLifetime, None) => continue,
+            };
 
-        //if self.iteration < new_iteration {
-        //self.iteration = new_iteration;
-        //}
+            let time = AnimationTime {
+                cycle_ms,
+                total_ms: elapsed_ms,
+            };
 
-        let cycle_ms = (elapsed_ms as u32 + self.animation_offset_ms) % self.duration_ms;
+            for (animation, start_color) in track.animations.iter().zip(&mut track.start_colors) {
+                data.start_color = *start_color;
+                animation.animate(data, &time, rng);
+                *start_color = data.start_color;
+            }
+        }
+    }
+}
 
-        let time = AnimationTime {
-            cycle_ms,
-            total_ms: elapsed_ms,
-        };
+impl TrackState {
+    /// None after the last loop. The start offset only shifts the phase, not the loop count.
+    fn cycle_ms(&self, elapsed_ms: u128) -> Option<u32> {
+        let duration_ms = self.duration_ms as u128;
 
-        for animation in self.animations.iter() {
-            animation.animate(data, &time);
+        if let Some(loop_count) = self.loop_count {
+            if loop_count as u128 <= elapsed_ms / duration_ms {
+                return None;
+            }
         }
+
+        let track_ms = elapsed_ms + self.animation_offset_ms as u128;
+        let cycle_ms = (track_ms % duration_ms) as u32;
+
+        if self.play_mode == PlayMode::PingPong && (track_ms / duration_ms) % 2 == 1 {
+            return Some(self.duration_ms - 1 - cycle_ms);
+        }
+
+        Some(cycle_ms)
     }
 }
 
-impl AnimationOptions {
+impl AnimationTrack {
+    /// Loops forever.
     pub fn new(
         duration_ms: u32,
         start_at: StartAnimationAt,
@@ -84,6 +173,144 @@ impl AnimationOptions {
             duration_ms,
             start_at,
             animations: Rc::new(animations),
+            play_mode: PlayMode::Loop,
+            loop_count: None,
+            timing: AnimationTiming::Elapsed,
+        }
+    }
+
+    /// Plays once over the lifetime of the particle, see AnimationTiming::OverLifetime.
+    pub fn over_lifetime(duration_ms: u32, animations: Vec<Box<dyn Animate>>) -> Self {
+        Self {
+            timing: AnimationTiming::OverLifetime,
+            ..Self::new(duration_ms, StartAnimationAt::Zero, animations)
         }
     }
 }
+
+impl AnimationOptions {
+    /// Single track that loops forever.
+    pub fn new(
+        duration_ms: u32,
+        start_at: StartAnimationAt,
+        animations: Vec<Box<dyn Animate>>,
+    ) -> Self {
+        Self::with_tracks(vec![AnimationTrack::new(duration_ms, start_at, animations)])
+    }
+
+    pub fn with_tracks(tracks: Vec<AnimationTrack>) -> Self {
+        Self { tracks }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AnimationHandler, AnimationOptions, AnimationTrack, PlayMode, StartAnimationAt};
+    use crate::animations::animation::{Animate, AnimationData, AnimationTime};
+    use crate::rng::Rng;
+    use macroquad::prelude::Color;
+
+    /// Writes the cycle into the radius.
+    struct CycleRadius;
+
+    impl Animate for CycleRadius {
+        fn animate(&self, data: &mut AnimationData, time: &AnimationTime, _: &Rng) {
+            data.radius = time.cycle_ms as f32;
+        }
+    }
+
+    /// Writes the cycle into vx.
+    struct CycleVelocity;
+
+    impl Animate for CycleVelocity {
+        fn animate(&self, data: &mut AnimationData, time: &AnimationTime, _: &Rng) {
+            data.vx = time.cycle_ms as f32;
+        }
+    }
+
+    fn animate(
+        handler: &mut AnimationHandler,
+        elapsed_ms: u128,
+        lifetime_ms: Option<u128>,
+    ) -> AnimationData {
+        let mut data = AnimationData {
+            color: Color::from_rgba(0, 0, 0, 255),
+            radius: -1.,
+            vx: -1.,
+            vy: 0.,
+            start_color: None,
+        };
+
+        handler.animate(&mut data, elapsed_ms, lifetime_ms, &Rng::new(1));
+        data
+    }
+
+    #[test]
+    fn runs_tracks_independently() {
+        let mut flicker =
+            AnimationTrack::new(300, StartAnimationAt::Zero, vec![Box::new(CycleRadius)]);
+        flicker.play_mode = PlayMode::PingPong;
+        flicker.loop_count = Some(2);
+
+        let fade = AnimationTrack::new(4000, StartAnimationAt::Zero, vec![Box::new(CycleVelocity)]);
+
+        let options = Some(AnimationOptions::with_tracks(vec![flicker, fade]));
+        let mut handler = AnimationHandler::new(&options, &Rng::new(1)).unwrap();
+
+        let data = animate(&mut handler, 100, None);
+        assert_eq!((100., 100.), (data.radius, data.vx));
+
+        // Second iteration of the flicker plays backwards.
+        let data = animate(&mut handler, 400, None);
+        assert_eq!((199., 400.), (data.radius, data.vx));
+
+        // The flicker stopped after two iterations.
+        let data = animate(&mut handler, 4100, None);
+        assert_eq!((-1., 100.), (data.radius, data.vx));
+    }
+
+    #[test]
+    fn offsets_without_skipping_loops() {
+        let mut once = AnimationTrack::new(
+            300,
+            StartAnimationAt::RangeMs(500, 501),
+            vec![Box::new(CycleRadius)],
+        );
+        once.loop_count = Some(1);
+
+        let options = Some(AnimationOptions::with_tracks(vec![once]));
+        let mut handler = AnimationHandler::new(&options, &Rng::new(1)).unwrap();
+
+        // Starts 500 ms in, still plays for a whole duration.
+        assert_eq!(200., animate(&mut handler, 0, None).radius);
+        assert_eq!(99., animate(&mut handler, 199, None).radius);
+        assert_eq!(-1., animate(&mut handler, 300, None).radius);
+    }
+
+    #[test]
+    fn survives_zero_duration() {
+        let mut empty =
+            AnimationTrack::new(0, StartAnimationAt::Random, vec![Box::new(CycleRadius)]);
+        empty.play_mode = PlayMode::PingPong;
+
+        let options = Some(AnimationOptions::with_tracks(vec![empty]));
+        let mut handler = AnimationHandler::new(&options, &Rng::new(1)).unwrap();
+
+        assert_eq!(0., animate(&mut handler, 0, None).radius);
+        assert_eq!(0., animate(&mut handler, 1234, None).radius);
+    }
+
+    #[test]
+    fn scales_to_lifetime() {
+        let size_over_life = AnimationTrack::over_lifetime(1000, vec![Box::new(CycleRadius)]);
+        let options = Some(AnimationOptions::with_tracks(vec![size_over_life]));
+        let mut handler = AnimationHandler::new(&options, &Rng::new(1)).unwrap();
+
+        assert_eq!(250., animate(&mut handler, 500, Some(2000)).radius);
+        assert_eq!(250., animate(&mut handler, 1000, Some(4000)).radius);
+        assert_eq!(999., animate(&mut handler, 5000, Some(4000)).radius);
+
+        // Lives forever.
+        assert_eq!(-1., animate(&mut handler, 500, None).radius);
+    }
+}
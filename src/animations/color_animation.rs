@@ -3,6 +3,8 @@ use macroquad::prelude::Color;
 use super::animation::Animate;
 use super::animation::AnimationData;
 use super::animation::AnimationTime;
+use crate::easing::{lerp_color, tween, Easing};
+use crate::rng::Rng;
 
 #[derive(Clone, Debug)]
 pub struct DuoColorAnimation {
@@ -10,6 +12,7 @@ pub struct DuoColorAnimation {
     pub color_to: Color,
     pub from_ms: u32,
     pub until_ms: u32,
+    pub easing: Easing,
 }
 
 #[derive(Clone, Debug)]
@@ -17,50 +20,76 @@ pub struct MonoColorAnimation {
     pub color: Color,
     pub from_ms: u32,
     pub until_ms: u32,
+    pub easing: Easing,
 }
 
 impl Animate for DuoColorAnimation {
-    fn animate(&self, data: &mut AnimationData, time: &AnimationTime) {
-        if time.cycle_ms < self.from_ms || self.until_ms <= time.cycle_ms {
+    fn animate(&self, data: &mut AnimationData, time: &AnimationTime, _: &Rng) {
+        let Some(fraction) = tween(time.cycle_ms, self.from_ms, self.until_ms, self.easing) else {
             return;
-        }
-
-        let delta_current = time.cycle_ms - self.from_ms;
-        let delta_max = self.until_ms - self.from_ms;
+        };
 
-        // calculate percent from 0..1
-        let fraction = delta_current as f32 / delta_max as f32;
-        data.color.r = self.color_from.r + fraction * (self.color_to.r - self.color_from.r);
-        data.color.g = self.color_from.g + fraction * (self.color_to.g - self.color_from.g);
-        data.color.b = self.color_from.b + fraction * (self.color_to.b - self.color_from.b);
-        data.color.a = self.color_from.a + fraction * (self.color_to.a - self.color_from.a);
+        data.color = lerp_color(self.color_from, self.color_to, fraction);
     }
 }
 
 impl Animate for MonoColorAnimation {
-    fn animate(&self, data: &mut AnimationData, time: &AnimationTime) {
-        if time.cycle_ms < self.from_ms || self.until_ms <= time.cycle_ms {
+    fn animate(&self, data: &mut AnimationData, time: &AnimationTime, _: &Rng) {
+        let Some(fraction) = tween(time.cycle_ms, self.from_ms, self.until_ms, self.easing) else {
+            data.start_color = None;
             return;
-        }
+        };
+
+        // Fades from the colour the particle had when the window opened.
+        let start = *data.start_color.get_or_insert(data.color);
+        data.color = lerp_color(start, self.color, fraction);
+    }
+}
 
-        let delta_current = time.cycle_ms - self.from_ms;
-        let delta_max = self.until_ms - self.from_ms;
-        let fraction = delta_current as f32 / delta_max as f32;
+#[cfg(test)]
+mod test {
+    use super::MonoColorAnimation;
+    use crate::animations::animation::{Animate, AnimationData, AnimationTime};
+    use crate::easing::Easing;
+    use crate::rng::Rng;
+    use macroquad::prelude::Color;
 
-        let fraction_remainder = 1. - fraction;
-        let delta_r = (self.color.r - data.color.r) / fraction_remainder;
-        let delta_g = (self.color.g - data.color.g) / fraction_remainder;
-        let delta_b = (self.color.b - data.color.b) / fraction_remainder;
-        let delta_a = (self.color.a - data.color.a) / fraction_remainder;
+    #[test]
+    fn overshoots_from_start_color() {
+        let animation = MonoColorAnimation {
+            color: Color::new(1., 1., 1., 1.),
+            from_ms: 0,
+            until_ms: 100,
+            easing: Easing::ElasticOut,
+        };
+        let mut data = AnimationData {
+            color: Color::new(0., 0., 0., 1.),
+            radius: 1.,
+            vx: 0.,
+            vy: 0.,
+            start_color: None,
+        };
+        let rng = Rng::new(1);
 
-        let from_r = (self.color.r - delta_r).abs();
-        let from_g = (self.color.g - delta_g).abs();
-        let from_b = (self.color.b - delta_b).abs();
-        let from_a = (self.color.a - delta_a).abs();
+        for cycle_ms in 0..100 {
+            let time = AnimationTime {
+                cycle_ms,
+                total_ms: cycle_ms as u128,
+            };
+            animation.animate(&mut data, &time, &rng);
+
+            let fraction = Easing::ElasticOut.ease(cycle_ms as f32 / 100.);
+            assert!(data.color.r.is_finite());
+            assert!((fraction - data.color.r).abs() < 0.001);
+            assert_eq!(1., data.color.a);
+        }
 
-        data.color.r = from_r + fraction * (self.color.r - from_r); // - data.color.r);
-        data.color.g = from_g + fraction * (self.color.g - from_g); // - data.color.g);
-        data.color.b = from_b + fraction * (self.color.b - from_b); // - data.color.b);
-        data.color.a = from_a + fraction * (self.color.a - from_a); // - data.color.a);
+        // Closing the window forgets the start colour.
+        let time = AnimationTime {
+            cycle_ms: 100,
+            total_ms: 100,
+        };
+        animation.animate(&mut data, &time, &rng);
+        assert_eq!(None, data.start_color);
     }
 }
@@ -0,0 +1,25 @@
+use super::animation::Animate;
+use super::animation::AnimationData;
+use super::animation::AnimationTime;
+use crate::easing::{tween, Easing};
+use crate::gradient::Gradient;
+use crate::rng::Rng;
+
+/// Runs through every stop of the gradient between from_ms and until_ms.
+#[derive(Clone, Debug)]
+pub struct GradientAnimation {
+    pub gradient: Gradient,
+    pub from_ms: u32,
+    pub until_ms: u32,
+    pub easing: Easing,
+}
+
+impl Animate for GradientAnimation {
+    fn animate(&self, data: &mut AnimationData, time: &AnimationTime, _: &Rng) {
+        let Some(fraction) = tween(time.cycle_ms, self.from_ms, self.until_ms, self.easing) else {
+            return;
+        };
+
+        data.color = self.gradient.color_at(fraction);
+    }
+}
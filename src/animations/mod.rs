@@ -1,5 +1,6 @@
 pub mod animation;
 pub mod animation_handler;
 pub mod color_animation;
+pub mod gradient_animation;
 pub mod size_animation;
 pub mod stray_animation;
@@ -1,6 +1,8 @@
 use super::animation::Animate;
 use super::animation::AnimationData;
 use super::animation::AnimationTime;
+use crate::easing::{lerp, tween, Easing};
+use crate::rng::Rng;
 
 #[derive(Clone, Debug)]
 pub struct SizeAnimation {
@@ -8,19 +10,15 @@ pub struct SizeAnimation {
     pub end_radius: f32,
     pub from_ms: u32,
     pub until_ms: u32,
+    pub easing: Easing,
 }
 
 impl Animate for SizeAnimation {
-    fn animate(&self, data: &mut AnimationData, time: &AnimationTime) {
-        if time.cycle_ms < self.from_ms || self.until_ms <= time.cycle_ms {
+    fn animate(&self, data: &mut AnimationData, time: &AnimationTime, _: &Rng) {
+        let Some(fraction) = tween(time.cycle_ms, self.from_ms, self.until_ms, self.easing) else {
             return;
-        }
+        };
 
-        let delta_current = time.cycle_ms - self.from_ms;
-        let delta_max = self.until_ms - self.from_ms;
-
-        // calculate percent
-        let fraction = delta_current as f32 / delta_max as f32;
-        data.radius = self.start_radius + fraction * (self.end_radius - self.start_radius);
+        data.radius = lerp(self.start_radius, self.end_radius, fraction);
     }
 }
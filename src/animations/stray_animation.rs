@@ -1,7 +1,7 @@
 use super::animation::Animate;
 use super::animation::AnimationData;
 use super::animation::AnimationTime;
-use macroquad::prelude::rand;
+use crate::rng::Rng;
 
 pub struct StrayAnimation {
     from_ms: u32,
@@ -20,12 +20,12 @@ impl StrayAnimation {
 }
 
 impl Animate for StrayAnimation {
-    fn animate(&self, data: &mut AnimationData, time: &AnimationTime) {
+    fn animate(&self, data: &mut AnimationData, time: &AnimationTime, rng: &Rng) {
         if time.cycle_ms < self.from_ms || self.until_ms <= time.cycle_ms {
             return;
         }
 
-        let stray = rand::gen_range(-self.strayness_radians, self.strayness_radians);
+        let stray = rng.gen_range(-self.strayness_radians, self.strayness_radians);
         data.vx = (data.vx * stray.cos()) - (data.vy * stray.sin());
         data.vy = (data.vx * stray.sin()) + (data.vy * stray.cos());
     }
@@ -0,0 +1,182 @@
+/// What happens to a particle that crosses an edge of the grid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoundaryMode {
+    /// Reflects off the edge with the elasticity of the particle.
+    Bounce,
+    /// Comes back in at the opposite edge.
+    Wrap,
+    /// Removes the particle.
+    Kill,
+    /// Stops at the edge, movement along the edge continues.
+    Clamp,
+    /// Sticks to the edge, friction is the fraction of the velocity along the edge lost per second.
+    Absorb { friction: f32 },
+    /// Lets the particle leave the grid. Grid particles can't live outside of the grid, so they
+    /// are removed like with Kill.
+    Open,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boundaries {
+    pub left: BoundaryMode,
+    pub right: BoundaryMode,
+    pub top: BoundaryMode,
+    pub bottom: BoundaryMode,
+}
+
+pub struct BoundaryData {
+    pub x: f32,
+    pub y: f32,
+    /// px/s
+    pub vx: f32,
+    /// px/s
+    pub vy: f32,
+    /// number between 0 and 1.
+    pub elasticity: f32,
+}
+
+impl Boundaries {
+    pub fn all(mode: BoundaryMode) -> Self {
+        Self {
+            left: mode,
+            right: mode,
+            top: mode,
+            bottom: mode,
+        }
+    }
+
+    /**
+     * Keeps the particle within 0..=max_x and 0..=max_y, dt in seconds.
+     * Returns false if the particle has to be removed, a particle past an open edge is kept.
+     */
+    pub fn apply(&self, data: &mut BoundaryData, max_x: f32, max_y: f32, dt: f32) -> bool {
+        let elasticity = data.elasticity;
+        let x_edge = apply_axis(
+            &mut data.x,
+            &mut data.vx,
+            max_x,
+            self.left,
+            self.right,
+            elasticity,
+        );
+        let y_edge = apply_axis(
+            &mut data.y,
+            &mut data.vy,
+            max_y,
+            self.top,
+            self.bottom,
+            elasticity,
+        );
+
+        if x_edge == Some(BoundaryMode::Kill) || y_edge == Some(BoundaryMode::Kill) {
+            return false;
+        }
+
+        // Friction along the absorbing edge.
+        if let Some(BoundaryMode::Absorb { friction }) = x_edge {
+            data.vy *= (1. - friction).powf(dt);
+        }
+
+        if let Some(BoundaryMode::Absorb { friction }) = y_edge {
+            data.vx *= (1. - friction).powf(dt);
+        }
+
+        true
+    }
+}
+
+/// Returns the mode of the edge the position crossed.
+fn apply_axis(
+    position: &mut f32,
+    velocity: &mut f32,
+    max: f32,
+    low: BoundaryMode,
+    high: BoundaryMode,
+    elasticity: f32,
+) -> Option<BoundaryMode> {
+    let (mode, edge, inwards) = if *position < 0. {
+        (low, 0., 1.)
+    } else if max < *position {
+        (high, max, -1.)
+    } else {
+        return None;
+    };
+
+    match mode {
+        BoundaryMode::Bounce => {
+            *position = edge;
+            *velocity = velocity.abs() * elasticity * inwards;
+        }
+        BoundaryMode::Wrap => {
+            *position = position.rem_euclid(max);
+        }
+        BoundaryMode::Clamp | BoundaryMode::Absorb { .. } => {
+            *position = edge;
+
+            // Only keeps the velocity away from the edge.
+            if *velocity * inwards < 0. {
+                *velocity = 0.;
+            }
+        }
+        BoundaryMode::Kill | BoundaryMode::Open => {}
+    }
+
+    Some(mode)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Boundaries, BoundaryData, BoundaryMode};
+
+    fn data(x: f32, vx: f32) -> BoundaryData {
+        BoundaryData {
+            x,
+            y: 50.,
+            vx,
+            vy: 10.,
+            elasticity: 0.5,
+        }
+    }
+
+    #[test]
+    fn applies_modes_per_edge() {
+        let boundaries = Boundaries {
+            left: BoundaryMode::Wrap,
+            right: BoundaryMode::Bounce,
+            top: BoundaryMode::Open,
+            bottom: BoundaryMode::Kill,
+        };
+
+        let mut wrapped = data(-5., -20.);
+        assert!(boundaries.apply(&mut wrapped, 100., 100., 0.01));
+        assert_eq!(95., wrapped.x);
+        assert_eq!(-20., wrapped.vx);
+
+        let mut bounced = data(105., 20.);
+        assert!(boundaries.apply(&mut bounced, 100., 100., 0.01));
+        assert_eq!(100., bounced.x);
+        assert_eq!(-10., bounced.vx);
+
+        let mut open = data(50., 0.);
+        open.y = -20.;
+        assert!(boundaries.apply(&mut open, 100., 100., 0.01));
+        assert_eq!(-20., open.y);
+
+        let mut killed = data(50., 0.);
+        killed.y = 120.;
+        assert!(!boundaries.apply(&mut killed, 100., 100., 0.01));
+    }
+
+    #[test]
+    fn absorbs_with_friction() {
+        let boundaries = Boundaries::all(BoundaryMode::Absorb { friction: 0.75 });
+        let mut absorbed = data(50., 10.);
+        absorbed.y = 101.;
+        absorbed.vy = 40.;
+
+        assert!(boundaries.apply(&mut absorbed, 100., 100., 1.));
+        assert_eq!(100., absorbed.y);
+        assert_eq!(0., absorbed.vy);
+        assert_eq!(2.5, absorbed.vx);
+    }
+}
@@ -0,0 +1,116 @@
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Time source of the simulation, every elapsed time (particles, emitters, forces, animations and
+/// trails) is derived from it.
+pub trait Clock {
+    /// Time since the clock started.
+    fn elapsed(&self) -> Duration;
+
+    /// Gets called once at the start of every grid step.
+    fn tick(&mut self) {}
+}
+
+impl Debug for dyn Clock {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Clock")
+    }
+}
+
+/// Wall-clock time.
+pub struct RealTimeClock {
+    start: Instant,
+}
+
+impl RealTimeClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for RealTimeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for RealTimeClock {
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Only moves when advanced by hand. Keep a clone around to advance the clock after moving it into
+/// the grid.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    elapsed: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.set(self.elapsed.get() + duration);
+    }
+
+    /// Jumps to elapsed, times before the current one are ignored since the clock never goes back.
+    pub fn set(&self, elapsed: Duration) {
+        self.elapsed.set(self.elapsed.get().max(elapsed));
+    }
+}
+
+impl Clock for ManualClock {
+    fn elapsed(&self) -> Duration {
+        self.elapsed.get()
+    }
+}
+
+/// Advances with the same amount of time on every tick, regardless of the real frame time.
+#[derive(Debug)]
+pub struct FixedStepClock {
+    step: Duration,
+    elapsed: Duration,
+}
+
+impl FixedStepClock {
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl Clock for FixedStepClock {
+    fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    fn tick(&mut self) {
+        self.elapsed += self.step;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Clock, ManualClock};
+    use std::time::Duration;
+
+    #[test]
+    fn never_moves_backwards() {
+        let clock = ManualClock::new();
+        clock.set(Duration::from_millis(500));
+        clock.set(Duration::from_millis(200));
+        assert_eq!(Duration::from_millis(500), clock.elapsed());
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(Duration::from_millis(600), clock.elapsed());
+    }
+}
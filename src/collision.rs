@@ -1,6 +1,177 @@
+/// Circle taking part in a collision, x and y are the center.
 pub struct CollisionData {
-    pub new_x: f32,
-    pub new_y: f32,
-    pub end_new_x: f32,
-    pub end_new_y: f32,
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub radius: f32,
+    pub mass: f32,
+    /// number between 0 and 1.
+    pub elasticity: f32,
+}
+
+/**
+ * Resolves the collision between two circles along the contact normal.
+ * The overlap is corrected by moving both circles apart (the lighter one moves the most) and an
+ * impulse is applied on the normal, the tangential velocity is kept. Momentum is conserved.
+ * Returns false if the circles don't overlap.
+ */
+pub fn resolve_collision(a: &mut CollisionData, b: &mut CollisionData) -> bool {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let min_distance = a.radius + b.radius;
+    let distance_pow = dx.powi(2) + dy.powi(2);
+
+    if min_distance.powi(2) <= distance_pow {
+        return false;
+    }
+
+    let distance = distance_pow.sqrt();
+
+    // Exactly on top of each other, any direction will do.
+    let (nx, ny) = if distance == 0. {
+        (1., 0.)
+    } else {
+        (dx / distance, dy / distance)
+    };
+
+    let inverse_mass_a = 1. / a.mass;
+    let inverse_mass_b = 1. / b.mass;
+    let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+
+    let correction = (min_distance - distance) / inverse_mass_sum;
+    a.x -= nx * correction * inverse_mass_a;
+    a.y -= ny * correction * inverse_mass_a;
+    b.x += nx * correction * inverse_mass_b;
+    b.y += ny * correction * inverse_mass_b;
+
+    apply_impulse(a, b, nx, ny);
+    true
+}
+
+/// Bounces two touching circles off each other, without moving them.
+pub fn resolve_impact(a: &mut CollisionData, b: &mut CollisionData) {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+
+    if distance == 0. {
+        return;
+    }
+
+    apply_impulse(a, b, dx / distance, dy / distance);
+}
+
+fn apply_impulse(a: &mut CollisionData, b: &mut CollisionData, nx: f32, ny: f32) {
+    let normal_velocity = (b.vx - a.vx) * nx + (b.vy - a.vy) * ny;
+
+    // Already moving apart.
+    if 0. <= normal_velocity {
+        return;
+    }
+
+    let inverse_mass_a = 1. / a.mass;
+    let inverse_mass_b = 1. / b.mass;
+    let elasticity = (a.elasticity + b.elasticity) / 2.;
+    let impulse = -(1. + elasticity) * normal_velocity / (inverse_mass_a + inverse_mass_b);
+
+    a.vx -= impulse * nx * inverse_mass_a;
+    a.vy -= impulse * ny * inverse_mass_a;
+    b.vx += impulse * nx * inverse_mass_b;
+    b.vy += impulse * ny * inverse_mass_b;
+}
+
+/**
+ * Sweeps circle a along dx, dy towards the resting circle b.
+ * Returns the fraction (0 to 1) of the movement at which they first touch, circles that already
+ * overlap or move apart are left to resolve_collision.
+ */
+pub fn time_of_impact(a: &CollisionData, dx: f32, dy: f32, b: &CollisionData) -> Option<f32> {
+    let px = a.x - b.x;
+    let py = a.y - b.y;
+    let min_distance = a.radius + b.radius;
+
+    let c = px.powi(2) + py.powi(2) - min_distance.powi(2);
+    let half_b = px * dx + py * dy;
+
+    if c <= 0. || 0. <= half_b {
+        return None;
+    }
+
+    let a = dx.powi(2) + dy.powi(2);
+    let discriminant = half_b.powi(2) - a * c;
+
+    if discriminant < 0. {
+        return None;
+    }
+
+    let fraction = (-half_b - discriminant.sqrt()) / a;
+    (fraction <= 1.).then_some(fraction)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{resolve_collision, time_of_impact, CollisionData};
+
+    fn circle(x: f32, vx: f32, mass: f32) -> CollisionData {
+        CollisionData {
+            x,
+            y: 100.,
+            vx,
+            vy: 0.,
+            radius: 2.5,
+            mass,
+            elasticity: 1.,
+        }
+    }
+
+    #[test]
+    fn ignores_separate_circles() {
+        let mut a = circle(100., 50., 1.);
+        let mut b = circle(105., -50., 1.);
+
+        assert!(!resolve_collision(&mut a, &mut b));
+        assert_eq!(50., a.vx);
+        assert_eq!(-50., b.vx);
+    }
+
+    #[test]
+    fn swaps_velocity_with_equal_mass() {
+        let mut a = circle(100., 50., 1.);
+        let mut b = circle(104., -50., 1.);
+
+        assert!(resolve_collision(&mut a, &mut b));
+        assert_eq!(-50., a.vx);
+        assert_eq!(50., b.vx);
+        assert!(5. <= b.x - a.x);
+    }
+
+    #[test]
+    fn finds_time_of_impact() {
+        let a = circle(0., 0., 1.);
+        let b = circle(100., 0., 1.);
+
+        // Touches after moving 95 of the 200 px.
+        assert_eq!(Some(0.475), time_of_impact(&a, 200., 0., &b));
+        assert_eq!(None, time_of_impact(&a, 90., 0., &b));
+        assert_eq!(None, time_of_impact(&a, -200., 0., &b));
+        assert_eq!(None, time_of_impact(&a, 0., 200., &b));
+    }
+
+    #[test]
+    fn conserves_momentum() {
+        let mut a = circle(100., 30., 1.);
+        let mut b = circle(103., -10., 4.);
+        a.y = 101.;
+        a.elasticity = 0.5;
+
+        let momentum_x = a.vx * a.mass + b.vx * b.mass;
+        let momentum_y = a.vy * a.mass + b.vy * b.mass;
+
+        assert!(resolve_collision(&mut a, &mut b));
+        assert!((momentum_x - (a.vx * a.mass + b.vx * b.mass)).abs() < 0.001);
+        assert!((momentum_y - (a.vy * a.mass + b.vy * b.mass)).abs() < 0.001);
+        // Off center hit, so the velocity changes along the normal.
+        assert!(a.vy != 0.);
+    }
 }
@@ -1,21 +1,32 @@
 use core::fmt::Formatter;
 use std::fmt::Debug;
 
-use crate::{grid::Grid, particle::Particle};
+use crate::particle::ParticleAttributes;
+use crate::particle_handle::ParticleHandle;
 
+/// Adds and removes particles while the simulation runs, coordinates are relative to the container.
 pub trait Container {
-    fn add_particle(&mut self, particle: &Particle);
-    fn remove_particle(&self, particle: &Particle);
-    fn start_render(&self);
-    fn stop_render(&self);
-    fn draw_dev_grid(&self, grid: &Grid);
-    fn show_dev_grid(&self);
-    fn hide_dev_grid(&self);
+    /// Spawns a particle with its top left corner at x, y, moving with vx, vy in px/s.
+    fn spawn_particle(
+        &mut self,
+        x: f32,
+        y: f32,
+        vx: f32,
+        vy: f32,
+        attributes: &ParticleAttributes,
+    ) -> ParticleHandle;
+    /// Returns false if the handle doesn't refer to a particle.
+    fn remove_particle(&mut self, handle: ParticleHandle) -> bool;
+    /// Removes the particles touching the circle around x, y and returns how many got removed.
+    fn remove_particles_in_radius(&mut self, x: f32, y: f32, radius: f32) -> usize;
+    /// Removes the particles touching the rectangle and returns how many got removed.
+    fn remove_particles_in_rect(&mut self, x: f32, y: f32, width: f32, height: f32) -> usize;
+    /// Removes every particle, emitters keep emitting.
+    fn clear(&mut self);
 }
 
 impl Debug for dyn Container {
-    fn fmt(&self, _: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        // Nothing
-        Ok(())
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "Container")
     }
 }
@@ -0,0 +1,208 @@
+use macroquad::prelude::Color;
+use std::f32::consts::PI;
+
+/// Maps the linear progress of an animation (0 to 1) onto a curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    /// Winds up past the start before moving to the end.
+    ElasticIn,
+    /// Overshoots the end and springs back.
+    ElasticOut,
+    BounceIn,
+    /// Bounces off the end like a dropped ball.
+    BounceOut,
+    /// Like css, control points (x1, y1, x2, y2) with x between 0 and 1.
+    CubicBezier(f32, f32, f32, f32),
+    /// Jumps in equal steps.
+    Steps(u32),
+}
+
+impl Easing {
+    /// fraction between 0 and 1, elastic and cubic bezier curves can leave that range in between.
+    pub fn ease(&self, fraction: f32) -> f32 {
+        let t = fraction.clamp(0., 1.);
+
+        match *self {
+            Easing::Linear => t,
+            Easing::QuadIn => t.powi(2),
+            Easing::QuadOut => 1. - (1. - t).powi(2),
+            Easing::QuadInOut if t < 0.5 => 2. * t.powi(2),
+            Easing::QuadInOut => 1. - (-2. * t + 2.).powi(2) / 2.,
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1. - (1. - t).powi(3),
+            Easing::CubicInOut if t < 0.5 => 4. * t.powi(3),
+            Easing::CubicInOut => 1. - (-2. * t + 2.).powi(3) / 2.,
+            Easing::ExpoIn | Easing::ExpoInOut | Easing::ElasticIn | Easing::ElasticOut
+                if t == 0. || t == 1. =>
+            {
+                t
+            }
+            Easing::ExpoIn => 2_f32.powf(10. * t - 10.),
+            Easing::ExpoOut if t == 1. => 1.,
+            Easing::ExpoOut => 1. - 2_f32.powf(-10. * t),
+            Easing::ExpoInOut if t < 0.5 => 2_f32.powf(20. * t - 10.) / 2.,
+            Easing::ExpoInOut => (2. - 2_f32.powf(-20. * t + 10.)) / 2.,
+            Easing::ElasticIn => {
+                -(2_f32.powf(10. * t - 10.)) * ((t * 10. - 10.75) * (2. * PI / 3.)).sin()
+            }
+            Easing::ElasticOut => {
+                2_f32.powf(-10. * t) * ((t * 10. - 0.75) * (2. * PI / 3.)).sin() + 1.
+            }
+            Easing::BounceIn => 1. - bounce_out(1. - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+            Easing::Steps(steps) => {
+                let steps = steps.max(1) as f32;
+                (t * steps).floor() / steps
+            }
+        }
+    }
+}
+
+/**
+ * Eased progress of cycle_ms within from_ms..until_ms, None outside of that window.
+ * Shared by every time based animation.
+ */
+pub fn tween(cycle_ms: u32, from_ms: u32, until_ms: u32, easing: Easing) -> Option<f32> {
+    if cycle_ms < from_ms || until_ms <= cycle_ms {
+        return None;
+    }
+
+    let delta_current = cycle_ms - from_ms;
+    let delta_max = until_ms - from_ms;
+
+    Some(easing.ease(delta_current as f32 / delta_max as f32))
+}
+
+pub fn lerp(from: f32, to: f32, fraction: f32) -> f32 {
+    from + fraction * (to - from)
+}
+
+pub fn lerp_color(from: Color, to: Color, fraction: f32) -> Color {
+    Color::new(
+        lerp(from.r, to.r, fraction),
+        lerp(from.g, to.g, fraction),
+        lerp(from.b, to.b, fraction),
+        lerp(from.a, to.a, fraction),
+    )
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+
+    if t < 1. / D1 {
+        N1 * t.powi(2)
+    } else if t < 2. / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t.powi(2) + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t.powi(2) + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t.powi(2) + 0.984375
+    }
+}
+
+/// Solves the curve for x with Newton's method, falls back to bisection on flat spots.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let curve = |p1: f32, p2: f32, t: f32| {
+        let u = 1. - t;
+        3. * u.powi(2) * t * p1 + 3. * u * t.powi(2) * p2 + t.powi(3)
+    };
+    let slope = |p1: f32, p2: f32, t: f32| {
+        let u = 1. - t;
+        3. * u.powi(2) * p1 + 6. * u * t * (p2 - p1) + 3. * t.powi(2) * (1. - p2)
+    };
+
+    let mut t = x;
+
+    for _ in 0..8 {
+        let error = curve(x1, x2, t) - x;
+        let derivative = slope(x1, x2, t);
+
+        if error.abs() < 1e-5 {
+            return curve(y1, y2, t);
+        }
+
+        if derivative.abs() < 1e-6 {
+            break;
+        }
+
+        t -= error / derivative;
+    }
+
+    let (mut low, mut high) = (0., 1.);
+    t = x;
+
+    for _ in 0..32 {
+        if curve(x1, x2, t) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+
+        t = (low + high) / 2.;
+    }
+
+    curve(y1, y2, t)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tween, Easing};
+
+    const EASINGS: [Easing; 16] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::ExpoIn,
+        Easing::ExpoOut,
+        Easing::ExpoInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::CubicBezier(0.25, 0.1, 0.25, 1.),
+        Easing::Steps(4),
+    ];
+
+    #[test]
+    fn starts_and_ends_in_place() {
+        for easing in EASINGS {
+            assert!(easing.ease(0.).abs() < 0.001, "{:?}", easing);
+            assert!((easing.ease(1.) - 1.).abs() < 0.001, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn eases_in_between() {
+        assert_eq!(0.25, Easing::QuadIn.ease(0.5));
+        assert_eq!(0.875, Easing::CubicOut.ease(0.5));
+        assert_eq!(0.5, Easing::Steps(4).ease(0.6));
+        assert!((Easing::CubicBezier(0., 0., 1., 1.).ease(0.3) - 0.3).abs() < 0.001);
+        assert!(1. < Easing::ElasticOut.ease(0.2));
+    }
+
+    #[test]
+    fn tweens_within_window() {
+        assert_eq!(None, tween(99, 100, 200, Easing::Linear));
+        assert_eq!(None, tween(200, 100, 200, Easing::Linear));
+        assert_eq!(Some(0.25), tween(150, 100, 200, Easing::QuadIn));
+    }
+}
@@ -0,0 +1,24 @@
+use super::emitter_animation::EmitterData;
+
+use super::emitter_animation::EmitterAnimate;
+use crate::easing::{tween, Easing};
+use crate::gradient::Gradient;
+use crate::rng::Rng;
+
+/// Colour of newly emitted particles, runs through every stop between from_ms and until_ms.
+pub struct EmitGradientAnimation {
+    pub from_ms: u32,
+    pub until_ms: u32,
+    pub gradient: Gradient,
+    pub easing: Easing,
+}
+
+impl EmitterAnimate for EmitGradientAnimation {
+    fn animate(&mut self, data: &mut EmitterData, cycle_ms: u32, _: &Rng) {
+        let Some(fraction) = tween(cycle_ms, self.from_ms, self.until_ms, self.easing) else {
+            return;
+        };
+
+        data.particle_color = self.gradient.color_at(fraction);
+    }
+}
@@ -1,21 +1,44 @@
 use crate::animations::animation::AnimationData;
 use crate::animations::animation_handler::AnimationHandler;
 use crate::animations::animation_handler::AnimationOptions;
+use crate::boundary::{Boundaries, BoundaryData};
+use crate::collision::{resolve_collision, CollisionData};
 use crate::emitters::emitter_animation_handler::EmitterAnimationHandler;
 use crate::force::force::ForceData;
 use crate::force::force_handler::ForceHandler;
+use crate::integrators::integrator::{IntegrationData, Integrator};
+use crate::movement_handler::MovementHandler;
+use crate::obstacles::obstacle::{resolve_contact, Obstacle};
+use crate::particle::Particle;
+use crate::point::Point;
+use crate::renderer::Renderer;
+use crate::rng::Rng;
+use crate::slot_map::{Id, SlotMap};
 use crate::trails::trail_animation::TrailData;
 use crate::trails::trail_handler::TrailHandler;
 use crate::Position;
 use macroquad::prelude::*;
+
+/// Emissions made up for in one step after a stall, older ones are skipped instead of spawning
+/// in one clump.
+const MAX_CATCH_UP_EMISSIONS: i32 = 8;
 use std::rc::Rc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use super::emitter_animation::EmitterData;
+use super::emitter_shape::EmitterShape;
+use super::sub_emitter::{
+    trigger_sub_emitters, SubEmission, SubEmitter, SubEmitterData, SubEmitterTrigger,
+};
 
 pub struct EmitterOptions {
     pub emitter_position: Position,
+    /// Length of EmitterShape::Line.
     pub emitter_diameter: f32,
+    pub emitter_shape: EmitterShape,
+    /// Emits away from the shape instead of in the direction of angle_degrees, angle_degrees
+    /// turns away from the normal.
+    pub emit_along_normal: bool,
     pub emitter_duration: Duration,
     pub angle_degrees: f32,
     /// Initial spread factor
@@ -28,25 +51,44 @@ pub struct EmitterOptions {
     pub particle_lifetime: Duration,
     pub particle_radius: f32,
     pub particle_mass: f32,
+    /// number between 0 and 1.
+    pub particle_elasticity: f32,
 
-    /// Newton force
+    /// px/s
     pub particle_speed: f32,
-    /// number between 0 and 1, e.g. 0.001
+    /// Fraction of the velocity lost per second, number between 0 and 1, e.g. 0.06
     pub particle_friction_coefficient: f32,
-    pub respect_grid_bounds: bool,
+    /// Edges of the grid, x and y of the particle center are checked.
+    pub boundaries: Boundaries,
     pub particle_animation_options: Option<AnimationOptions>,
     pub emitter_animation_handler: Option<EmitterAnimationHandler>,
     pub force_handler: Option<ForceHandler>,
     pub trail_handler: Option<TrailHandler>,
+    /// Moves the emitter over a flight path.
+    pub movement_handler: Option<MovementHandler>,
+    /// Makes angle_degrees relative to the travel direction of the movement handler, 0 emits
+    /// forwards and 180 backwards.
+    pub align_emission_to_movement: bool,
+    /// Uses the random number generator of the grid if None.
+    pub seed: Option<u64>,
+    /// Uses the integrator of the grid if None.
+    pub integrator: Option<Rc<dyn Integrator>>,
+    /// Inserts the particles into the spatial hash of the grid, so they collide with the grid
+    /// particles and the particles of other emitters with this flag. The particle diameter can't
+    /// exceed the possibility_side_length of the grid.
+    pub collide_with_grid: bool,
+    pub sub_emitters: Vec<SubEmitter>,
 }
 
 #[derive(Debug)]
 pub struct Emitter {
     emitter_diameter: f32,
+    emitter_shape: EmitterShape,
+    emit_along_normal: bool,
     x: f32,
     y: f32,
     grid_position: Position,
-    respect_grid_bounds: bool,
+    boundaries: Boundaries,
     angle_radians: f32,
     angle_emission_radians: f32,
     diffusion_radians: f32,
@@ -59,28 +101,51 @@ pub struct Emitter {
     particle_lifetime_ms: u128,
     particle_radius: f32,
     particle_mass: f32,
+    particle_elasticity: f32,
     particle_speed: f32,
     particle_friction_coefficient: f32,
     trail_handler: Option<TrailHandler>,
     particles: Vec<EmittedParticle>,
-    lifetime: Instant,
+    /// Index in particles of every living particle.
+    particle_slots: SlotMap<usize>,
+    /// Clock time at which the emitter was added.
+    created_at: Duration,
     emitter_duration: Duration,
     particle_animation_options: Option<AnimationOptions>,
     force_handler: Option<ForceHandler>,
     emitter_animation_handler: Option<EmitterAnimationHandler>,
+    movement_handler: Option<MovementHandler>,
+    align_emission_to_movement: bool,
+    rng: Rc<Rng>,
+    integrator: Rc<dyn Integrator>,
+    collide_with_grid: bool,
+    sub_emitters: Vec<SubEmitter>,
+    /// Emitters spawned by sub emitters, taken over by the grid.
+    sub_emissions: Vec<SubEmission>,
+    /// px/s, velocity inherited from the particle that spawned this emitter.
+    inherited_vx: f32,
+    inherited_vy: f32,
+    pub id: Id,
     pub delete: bool,
     pub particle_count: u32,
 }
 
+/// x and y are the center of the particle.
 #[derive(Debug)]
-struct EmittedParticle {
-    x: f32,
-    y: f32,
-    vx: f32,
-    vy: f32,
-    radius: f32,
-    lifetime: Rc<Instant>,
-    color: Color,
+pub struct EmittedParticle {
+    pub id: Id,
+    pub x: f32,
+    pub y: f32,
+    /// px/s
+    pub vx: f32,
+    /// px/s
+    pub vy: f32,
+    pub radius: f32,
+    pub mass: f32,
+    /// number between 0 and 1.
+    pub elasticity: f32,
+    pub spawned_at: Duration,
+    pub color: Color,
     trail_handler: Option<TrailHandler>,
     animation_handler: Option<AnimationHandler>,
 }
@@ -88,10 +153,19 @@ struct EmittedParticle {
 const INVERSE_RADIANS: f32 = -90_f32 * (std::f32::consts::PI / 181.0f32); // 0 deg will be emitting above
 
 impl Emitter {
-    pub fn new(grid_position: Position, options: EmitterOptions) -> Self {
+    pub fn new(
+        id: Id,
+        grid_position: Position,
+        options: EmitterOptions,
+        created_at: Duration,
+        grid_rng: Rc<Rng>,
+        grid_integrator: Rc<dyn Integrator>,
+    ) -> Self {
         let EmitterOptions {
             emitter_position,
             emitter_diameter,
+            emitter_shape,
+            emit_along_normal,
             emitter_duration,
             angle_degrees,
             diffusion_degrees,
@@ -103,13 +177,20 @@ impl Emitter {
             particle_lifetime,
             particle_radius,
             particle_mass,
+            particle_elasticity,
             particle_speed,
             particle_friction_coefficient,
-            respect_grid_bounds,
+            boundaries,
             particle_animation_options,
             emitter_animation_handler,
             force_handler,
             trail_handler,
+            movement_handler,
+            align_emission_to_movement,
+            seed,
+            integrator,
+            collide_with_grid,
+            sub_emitters,
         } = options;
 
         let angle_radians = angle_degrees.to_radians();
@@ -120,10 +201,12 @@ impl Emitter {
         Self {
             particles_per_emission,
             particles: Vec::new(),
+            particle_slots: SlotMap::default(),
             particle_color,
             particle_texture,
             diffusion_radians: diffusion_degrees.to_radians(),
             particle_mass,
+            particle_elasticity,
             particle_radius,
             x,
             y,
@@ -134,29 +217,41 @@ impl Emitter {
             particle_lifetime_ms: particle_lifetime.as_millis(),
             particle_count: 0,
             emitter_diameter,
+            emitter_shape,
+            emit_along_normal,
             emitter_duration,
-            lifetime: Instant::now(),
+            created_at,
             current_emission: -1,
             delay_between_emission_ms: delay_between_emission.as_millis(),
-            respect_grid_bounds,
+            boundaries,
             particle_friction_coefficient,
             particle_speed,
             particle_animation_options,
             emitter_animation_handler,
             force_handler,
             trail_handler,
+            movement_handler,
+            align_emission_to_movement,
+            rng: seed.map_or(grid_rng, |seed| Rc::new(Rng::new(seed))),
+            integrator: integrator.unwrap_or(grid_integrator),
+            collide_with_grid,
+            sub_emitters,
+            sub_emissions: Vec::new(),
+            inherited_vx: 0.,
+            inherited_vy: 0.,
+            id,
             delete: false,
         }
     }
 
-    fn animate_emitter(&mut self, elapsed_ms: u128) {
+    fn animate_emitter(&mut self, elapsed_ms: u128, dt: f32) {
         if let Some(anim_handler) = &mut self.emitter_animation_handler {
             let mut data = EmitterData {
                 delay_between_emission_ms: self.delay_between_emission_ms,
                 particle_speed: self.particle_speed,
                 particle_friction_coefficient: self.particle_friction_coefficient,
                 particles_per_emission: self.particles_per_emission,
-                respect_grid_bounds: self.respect_grid_bounds,
+                boundaries: self.boundaries,
                 emitter_diameter: self.emitter_diameter,
                 particle_color: self.particle_color,
                 emission_distortion: self.emission_distortion,
@@ -165,9 +260,10 @@ impl Emitter {
                 particle_radius: self.particle_radius,
                 x: self.x,
                 y: self.y,
+                dt,
             };
 
-            anim_handler.animate(&mut data, elapsed_ms);
+            anim_handler.animate(&mut data, elapsed_ms, &self.rng);
 
             self.angle_emission_radians = data.angle_radians + INVERSE_RADIANS;
             self.diffusion_radians = data.diffusion_radians;
@@ -179,23 +275,37 @@ impl Emitter {
         }
     }
 
-    pub fn emit(&mut self) {
-        let elapsed = self.lifetime.elapsed();
+    /// dt in seconds.
+    pub fn step(&mut self, now: Duration, dt: f32, obstacles: &[Box<dyn Obstacle>]) {
+        let elapsed = now.saturating_sub(self.created_at);
         let overdue = elapsed > self.emitter_duration;
         let emitter_elapsed_ms = elapsed.as_millis();
-        let new_emission = (emitter_elapsed_ms / self.delay_between_emission_ms) as i32;
+        let delay_ms = self.delay_between_emission_ms.max(1);
+        let new_emission = (emitter_elapsed_ms / delay_ms) as i32;
+
+        if let Some(movement_handler) = &mut self.movement_handler {
+            let Point(x, y) = movement_handler.travel(Point(self.x, self.y), dt);
+            self.x = x;
+            self.y = y;
+        }
+
+        // Catches up on emissions missed in between steps, so a low frame rate emits as much.
+        self.current_emission = self
+            .current_emission
+            .max(new_emission - MAX_CATCH_UP_EMISSIONS);
 
-        if !overdue && self.current_emission < new_emission {
-            self.current_emission = new_emission;
-            let lifetime = Rc::new(Instant::now());
+        while !overdue && self.current_emission < new_emission {
+            self.current_emission += 1;
             for _ in 0..self.particles_per_emission {
-                self.particles
-                    .push(self.create_particle(Rc::clone(&lifetime)));
+                let id = self.particle_slots.insert(self.particles.len());
+                let particle = self.create_particle(now, id);
+                self.trigger(SubEmitterTrigger::Birth, &particle);
+                self.particles.push(particle);
             }
         }
 
-        self.animate_emitter(emitter_elapsed_ms);
-        self.update_particles(emitter_elapsed_ms);
+        self.animate_emitter(emitter_elapsed_ms, dt);
+        self.update_particles(emitter_elapsed_ms, now, dt, obstacles);
 
         if self.particles.is_empty() && overdue {
             self.delete = true;
@@ -203,39 +313,61 @@ impl Emitter {
         self.particle_count = self.particles.len() as u32;
     }
 
-    fn update_particles(&mut self, emitter_elapsed_ms: u128) {
+    fn update_particles(
+        &mut self,
+        emitter_elapsed_ms: u128,
+        now: Duration,
+        dt: f32,
+        obstacles: &[Box<dyn Obstacle>],
+    ) {
+        let retained = (1. - self.particle_friction_coefficient).powf(dt);
+
         for i in (0..self.particles.len()).rev() {
             let mut particle = self.particles.swap_remove(i);
 
-            let x_force = particle.vx * self.particle_mass;
-            let y_force = particle.vy * self.particle_mass;
-
-            let x_friction = x_force * self.particle_friction_coefficient;
-            let y_friction = y_force * self.particle_friction_coefficient;
+            particle.vx *= retained;
+            particle.vy *= retained;
 
-            let vx = (x_force - x_friction) / self.particle_mass;
-            let vy = (y_force - y_friction) / self.particle_mass;
+            let mut data = IntegrationData {
+                x: particle.x,
+                y: particle.y,
+                vx: particle.vx,
+                vy: particle.vy,
+            };
 
-            if let Some(force_handler) = &mut self.force_handler {
-                let mut data = ForceData {
-                    x: particle.x,
-                    y: particle.y,
-                    vx,
-                    vy,
-                    radius: self.particle_radius,
-                    mass: self.particle_mass,
+            let force_handler = &self.force_handler;
+            let radius = self.particle_radius;
+            let mass = self.particle_mass;
+
+            self.integrator.integrate(&mut data, dt, &mut |state| {
+                let mut force_data = ForceData {
+                    x: state.x,
+                    y: state.y,
+                    vx: state.vx,
+                    vy: state.vy,
+                    radius,
+                    mass,
+                    ax: 0.,
+                    ay: 0.,
                 };
 
-                force_handler.apply(&mut data, emitter_elapsed_ms);
+                if let Some(force_handler) = force_handler {
+                    force_handler.apply(&mut force_data, emitter_elapsed_ms);
+                }
 
-                particle.vx = data.vx;
-                particle.vy = data.vy;
-            } else {
-                particle.vx = vx;
-                particle.vy = vy;
+                (force_data.ax, force_data.ay)
+            });
+
+            particle.x = data.x;
+            particle.y = data.y;
+            particle.vx = data.vx;
+            particle.vy = data.vy;
+
+            for obstacle in obstacles.iter() {
+                particle.handle_obstacle(obstacle.as_ref(), self.particle_friction_coefficient, dt);
             }
 
-            let particle_elapsed_ms = particle.lifetime.elapsed().as_millis();
+            let particle_elapsed_ms = now.saturating_sub(particle.spawned_at).as_millis();
 
             if let Some(animation_handler) = &mut particle.animation_handler {
                 let mut data: AnimationData = AnimationData {
@@ -243,85 +375,253 @@ impl Emitter {
                     color: particle.color,
                     vx: particle.vx,
                     vy: particle.vy,
+                    start_color: None,
                 };
 
-                animation_handler.animate(&mut data, particle_elapsed_ms);
+                animation_handler.animate(
+                    &mut data,
+                    particle_elapsed_ms,
+                    Some(self.particle_lifetime_ms),
+                    &self.rng,
+                );
                 particle.vx = data.vx;
                 particle.vy = data.vy;
                 particle.color = data.color;
                 particle.radius = data.radius;
             }
 
-            particle.x += particle.vx;
-            particle.y += particle.vy;
-
-            let x = particle.x + self.grid_position.x;
-            let y = particle.y + self.grid_position.y;
-
             if let Some(trail_handler) = &mut particle.trail_handler {
                 let data = TrailData {
                     radius: particle.radius,
                     color: particle.color,
-                    x_abs: x,
-                    y_abs: y,
+                    x: particle.x,
+                    y: particle.y,
                 };
 
                 trail_handler.animate(&data, particle_elapsed_ms);
             }
 
-            if let Some(texture) = self.particle_texture {
-                let side = particle.radius * 2.;
-                let dest_size = Some(Vec2::new(side, side));
+            let mut boundary_data = BoundaryData {
+                x: particle.x,
+                y: particle.y,
+                vx: particle.vx,
+                vy: particle.vy,
+                elasticity: particle.elasticity,
+            };
 
-                let params = DrawTextureParams {
-                    dest_size,
-                    ..Default::default()
-                };
+            let max_x = self.grid_position.width;
+            let max_y = self.grid_position.height;
 
-                draw_texture_ex(texture, x, y, particle.color, params);
-            } else {
-                draw_circle(x, y, particle.radius, particle.color);
+            if !self.boundaries.apply(&mut boundary_data, max_x, max_y, dt) {
+                self.trigger(SubEmitterTrigger::Death, &particle);
+                self.particle_slots.remove(particle.id);
+                continue; // removes particle.
             }
 
-            let diameter = particle.radius * 2.;
+            particle.x = boundary_data.x;
+            particle.y = boundary_data.y;
+            particle.vx = boundary_data.vx;
+            particle.vy = boundary_data.vy;
 
-            if self.respect_grid_bounds
-                && (particle.x < 0.
-                    || self.grid_position.width < particle.x + diameter
-                    || particle.y < 0.
-                    || self.grid_position.height < particle.y + diameter)
-            {
-                continue; // removes particle.
-            } else if particle_elapsed_ms <= self.particle_lifetime_ms {
+            if particle_elapsed_ms <= self.particle_lifetime_ms {
                 self.particles.push(particle);
+            } else {
+                self.trigger(SubEmitterTrigger::Death, &particle);
+                self.particle_slots.remove(particle.id);
             }
         }
+
+        // Updating reverses the order of the particles.
+        for (index, particle) in self.particles.iter().enumerate() {
+            self.particle_slots.set(particle.id, index);
+        }
+    }
+
+    pub fn particles(&self) -> &[EmittedParticle] {
+        &self.particles
+    }
+
+    pub fn particles_mut(&mut self) -> &mut [EmittedParticle] {
+        &mut self.particles
+    }
+
+    /// Index in particles(), None if the particle doesn't live anymore.
+    pub fn particle_index(&self, id: Id) -> Option<usize> {
+        self.particle_slots.get(id)
+    }
+
+    /// Returns false if the particle doesn't live anymore.
+    pub fn remove_particle(&mut self, id: Id) -> bool {
+        let Some(index) = self.particle_slots.remove(id) else {
+            return false;
+        };
+
+        self.particles.swap_remove(index);
+
+        if let Some(moved) = self.particles.get(index) {
+            self.particle_slots.set(moved.id, index);
+        }
+
+        self.particle_count = self.particles.len() as u32;
+        true
+    }
+
+    pub fn clear_particles(&mut self) {
+        self.particles.clear();
+        self.particle_slots.clear();
+        self.particle_count = 0;
+    }
+
+    /// Triggers the collision sub emitters of the particle at index.
+    pub fn handle_particle_collision(&mut self, index: usize) {
+        let data = self.particles[index].sub_emitter_data();
+        trigger_sub_emitters(
+            &self.sub_emitters,
+            SubEmitterTrigger::Collision,
+            &data,
+            &mut self.sub_emissions,
+        );
+    }
+
+    fn trigger(&mut self, trigger: SubEmitterTrigger, particle: &EmittedParticle) {
+        let data = particle.sub_emitter_data();
+        trigger_sub_emitters(&self.sub_emitters, trigger, &data, &mut self.sub_emissions);
+    }
+
+    pub fn take_sub_emissions(&mut self) -> Vec<SubEmission> {
+        std::mem::take(&mut self.sub_emissions)
+    }
+
+    /// Adds the velocity (px/s) to every particle emitted from now on.
+    pub fn inherit_velocity(&mut self, vx: f32, vy: f32) {
+        self.inherited_vx = vx;
+        self.inherited_vy = vy;
+    }
+
+    pub fn collides_with_grid(&self) -> bool {
+        self.collide_with_grid
     }
 
-    fn create_particle(&self, lifetime: Rc<Instant>) -> EmittedParticle {
-        let position = rand::gen_range(0., self.emitter_diameter);
-        let distortion = rand::gen_range(-self.emission_distortion, self.emission_distortion);
-        let x = (self.x + distortion) + position * self.angle_radians.cos();
-        let y = (self.y + distortion) + position * self.angle_radians.sin();
+    pub fn draw(&self, renderer: &mut dyn Renderer) {
+        for particle in self.particles.iter() {
+            let x = particle.x + self.grid_position.x;
+            let y = particle.y + self.grid_position.y;
 
-        let diffusion_delta = rand::gen_range(-self.diffusion_radians, self.diffusion_radians);
+            if let Some(trail_handler) = &particle.trail_handler {
+                trail_handler.draw(renderer, &self.grid_position);
+            }
+
+            if let Some(texture) = self.particle_texture {
+                let side = particle.radius * 2.;
+                let x = x - particle.radius;
+                let y = y - particle.radius;
+                renderer.draw_texture(texture, x, y, side, particle.color);
+            } else {
+                renderer.draw_circle(x, y, particle.radius, particle.color);
+            }
+        }
+    }
 
-        let angle_radians = self.angle_emission_radians + diffusion_delta;
-        let vx = self.particle_speed * angle_radians.cos();
-        let vy = self.particle_speed * angle_radians.sin();
+    fn create_particle(&self, spawned_at: Duration, id: Id) -> EmittedParticle {
+        let shape_point =
+            self.emitter_shape
+                .sample(&self.rng, self.angle_radians, self.emitter_diameter);
+        let distortion = self
+            .rng
+            .gen_range(-self.emission_distortion, self.emission_distortion);
+        let x = self.x + distortion + shape_point.x;
+        let y = self.y + distortion + shape_point.y;
+
+        let diffusion_delta = self
+            .rng
+            .gen_range(-self.diffusion_radians, self.diffusion_radians);
+
+        let mut angle_radians = self.angle_emission_radians + diffusion_delta;
+
+        if self.emit_along_normal {
+            angle_radians += shape_point.normal_radians - INVERSE_RADIANS;
+        } else if let Some(movement_handler) = &self.movement_handler {
+            if self.align_emission_to_movement && movement_handler.is_moving() {
+                angle_radians += movement_handler.heading() - INVERSE_RADIANS;
+            }
+        }
+        let vx = self.particle_speed * angle_radians.cos() + self.inherited_vx;
+        let vy = self.particle_speed * angle_radians.sin() + self.inherited_vy;
 
-        let animation_handler = AnimationHandler::new(&self.particle_animation_options);
+        let animation_handler = AnimationHandler::new(&self.particle_animation_options, &self.rng);
 
         EmittedParticle {
+            id,
             x,
             y,
             vx,
             vy,
-            lifetime,
+            spawned_at,
             radius: self.particle_radius,
+            mass: self.particle_mass,
+            elasticity: self.particle_elasticity,
             color: self.particle_color,
             trail_handler: self.trail_handler.clone(),
             animation_handler,
         }
     }
 }
+
+impl EmittedParticle {
+    pub fn collision_data(&self) -> CollisionData {
+        CollisionData {
+            x: self.x,
+            y: self.y,
+            vx: self.vx,
+            vy: self.vy,
+            radius: self.radius,
+            mass: self.mass,
+            elasticity: self.elasticity,
+        }
+    }
+
+    pub fn apply_collision_data(&mut self, data: &CollisionData) {
+        self.x = data.x;
+        self.y = data.y;
+        self.vx = data.vx;
+        self.vy = data.vy;
+    }
+
+    /// Bounces off the obstacle, friction is lost per second and dt in seconds.
+    /// Returns true if the particle touched the obstacle.
+    pub fn handle_obstacle(&mut self, obstacle: &dyn Obstacle, friction: f32, dt: f32) -> bool {
+        let mut data = self.collision_data();
+
+        let Some(contact) = obstacle.contact(data.x, data.y, data.radius) else {
+            return false;
+        };
+
+        resolve_contact(&mut data, &contact, friction, dt);
+        self.apply_collision_data(&data);
+        true
+    }
+
+    pub fn sub_emitter_data(&self) -> SubEmitterData {
+        SubEmitterData {
+            x: self.x,
+            y: self.y,
+            vx: self.vx,
+            vy: self.vy,
+            color: self.color,
+        }
+    }
+
+    /// Returns true if the particles collided.
+    pub fn handle_possible_collision(&mut self, other: &mut Particle) -> bool {
+        let mut data = self.collision_data();
+        let mut other_data = other.collision_data();
+
+        if !resolve_collision(&mut data, &mut other_data) {
+            return false;
+        }
+
+        self.apply_collision_data(&data);
+        other.apply_collision_data(&other_data);
+        true
+    }
+}
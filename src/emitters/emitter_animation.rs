@@ -1,8 +1,10 @@
+use crate::boundary::Boundaries;
+use crate::rng::Rng;
 use macroquad::prelude::Color;
 use std::fmt::Debug;
 
 pub trait EmitterAnimate {
-    fn animate(&mut self, data: &mut EmitterData, cycle_ms: u32);
+    fn animate(&mut self, data: &mut EmitterData, cycle_ms: u32, rng: &Rng);
 }
 
 impl Debug for dyn EmitterAnimate {
@@ -15,7 +17,7 @@ pub struct EmitterData {
     pub emitter_diameter: f32,
     pub x: f32,
     pub y: f32,
-    pub respect_grid_bounds: bool,
+    pub boundaries: Boundaries,
     pub angle_radians: f32,
     pub diffusion_radians: f32,
     pub particles_per_emission: u32,
@@ -24,8 +26,11 @@ pub struct EmitterData {
     pub particle_color: Color,
     pub particle_radius: f32,
 
-    /// Only on newly spawned particles
+    /// Only on newly spawned particles, px/s
     pub particle_speed: f32,
     /// Only on newly spawned particles
     pub particle_friction_coefficient: f32,
+
+    /// Seconds since the previous step, read only.
+    pub dt: f32,
 }
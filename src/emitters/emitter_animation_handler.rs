@@ -1,4 +1,5 @@
 use crate::emitters::emitter_animation::{EmitterAnimate, EmitterData};
+use crate::rng::Rng;
 
 #[derive(Debug)]
 pub struct EmitterAnimationHandler {
@@ -14,10 +15,10 @@ impl EmitterAnimationHandler {
         }
     }
 
-    pub fn animate(&mut self, data: &mut EmitterData, elapsed_ms: u128) {
+    pub fn animate(&mut self, data: &mut EmitterData, elapsed_ms: u128, rng: &Rng) {
         let cycle_ms = elapsed_ms as u32 % self.duration_ms;
         for animation in self.animations.iter_mut() {
-            animation.animate(data, cycle_ms);
+            animation.animate(data, cycle_ms, rng);
         }
     }
 }
@@ -0,0 +1,381 @@
+use crate::obstacles::polygon_obstacle::polygon_contains;
+use crate::obstacles::segment_obstacle::closest_point;
+use crate::point::Point;
+use crate::rng::Rng;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// Polygon points tried before falling back to the outline.
+const MAX_POLYGON_ATTEMPTS: u32 = 32;
+
+/// Area or outline particles spawn on, points are relative to the emitter position.
+#[derive(Debug, Clone)]
+pub enum EmitterShape {
+    /// Line of emitter_diameter, starting at the emitter and rotated by angle_degrees.
+    Line,
+    Point,
+    CircleArea {
+        radius: f32,
+    },
+    CircleEdge {
+        radius: f32,
+    },
+    Ring {
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+    /// Centered on the emitter.
+    Rectangle {
+        width: f32,
+        height: f32,
+    },
+    /// Closed area, convex or concave. The last point connects to the first one.
+    Polygon(PolygonShape),
+    /// Spread evenly over the length of the lines.
+    Polyline(Outline),
+}
+
+/// Connected segments, prepared once so sampling doesn't walk the points for every particle.
+#[derive(Debug, Clone)]
+pub struct Outline {
+    segments: Vec<(Point, Point)>,
+    total_length: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolygonShape {
+    points: Vec<Point>,
+    outline: Outline,
+    /// Sign of the area, tells which side of the edges is outside.
+    winding: f32,
+    min: Point,
+    max: Point,
+}
+
+/// Spawn point relative to the emitter.
+pub struct ShapePoint {
+    pub x: f32,
+    pub y: f32,
+    /// Direction in radians facing away from the shape, 0 is to the right.
+    pub normal_radians: f32,
+}
+
+impl EmitterShape {
+    /// angle_radians and diameter only apply to EmitterShape::Line.
+    pub fn sample(&self, rng: &Rng, angle_radians: f32, diameter: f32) -> ShapePoint {
+        match self {
+            EmitterShape::Line => {
+                let position = rng.gen_range(0., diameter);
+
+                ShapePoint {
+                    x: position * angle_radians.cos(),
+                    y: position * angle_radians.sin(),
+                    normal_radians: angle_radians - FRAC_PI_2,
+                }
+            }
+            // Every direction faces away from a point.
+            EmitterShape::Point => ShapePoint {
+                x: 0.,
+                y: 0.,
+                normal_radians: rng.gen_range(-PI, PI),
+            },
+            EmitterShape::CircleArea { radius } => {
+                // Square root spreads the points evenly instead of bunching them in the center.
+                radial_point(rng, radius * rng.gen_range(0_f32, 1.).sqrt())
+            }
+            EmitterShape::CircleEdge { radius } => radial_point(rng, *radius),
+            EmitterShape::Ring {
+                inner_radius,
+                outer_radius,
+            } => {
+                let inner = inner_radius.powi(2);
+                let outer = outer_radius.powi(2);
+                radial_point(rng, rng.gen_range(inner, outer).sqrt())
+            }
+            EmitterShape::Rectangle { width, height } => {
+                let half_width = width / 2.;
+                let half_height = height / 2.;
+                let x = rng.gen_range(-half_width, half_width);
+                let y = rng.gen_range(-half_height, half_height);
+
+                // Faces away from the closest edge.
+                let edges = [
+                    (half_width - x, 0.),
+                    (x + half_width, PI),
+                    (half_height - y, FRAC_PI_2),
+                    (y + half_height, -FRAC_PI_2),
+                ];
+                let (_, normal_radians) = edges
+                    .into_iter()
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+                    .unwrap();
+
+                ShapePoint {
+                    x,
+                    y,
+                    normal_radians,
+                }
+            }
+            EmitterShape::Polygon(polygon) => polygon.sample(rng),
+            EmitterShape::Polyline(outline) => {
+                let Some((point, (start, end))) = outline.sample(rng) else {
+                    return ShapePoint {
+                        x: 0.,
+                        y: 0.,
+                        normal_radians: -FRAC_PI_2,
+                    };
+                };
+
+                // Left of the travel direction, so a line from left to right faces up.
+                ShapePoint {
+                    x: point.0,
+                    y: point.1,
+                    normal_radians: edge_normal(&start, &end, 1.),
+                }
+            }
+        }
+    }
+
+    pub fn polygon(points: Vec<Point>) -> Self {
+        EmitterShape::Polygon(PolygonShape::new(points))
+    }
+
+    pub fn polyline(points: &[Point]) -> Self {
+        EmitterShape::Polyline(Outline::open(points))
+    }
+}
+
+impl Outline {
+    /// Segments between the points, the last point stays unconnected.
+    pub fn open(points: &[Point]) -> Self {
+        let segments = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+        Self::from_segments(segments)
+    }
+
+    /// Connects the last point back to the first one.
+    pub fn closed(points: &[Point]) -> Self {
+        let next = points.iter().cycle().skip(1);
+        let segments = points.iter().copied().zip(next.copied()).collect();
+        Self::from_segments(segments)
+    }
+
+    fn from_segments(segments: Vec<(Point, Point)>) -> Self {
+        let total_length = segments.iter().map(segment_length).sum();
+
+        Self {
+            segments,
+            total_length,
+        }
+    }
+
+    /// Point spread evenly over the length and the segment it lies on, None without segments.
+    fn sample(&self, rng: &Rng) -> Option<(Point, (Point, Point))> {
+        let mut segment = *self.segments.last()?;
+        let mut remaining = rng.gen_range(0., self.total_length);
+
+        for candidate in self.segments.iter() {
+            if remaining <= segment_length(candidate) {
+                segment = *candidate;
+                break;
+            }
+
+            remaining -= segment_length(candidate);
+        }
+
+        let (start, end) = segment;
+        let fraction = (remaining / segment_length(&segment).max(f32::EPSILON)).min(1.);
+        let point = Point(
+            start.0 + (end.0 - start.0) * fraction,
+            start.1 + (end.1 - start.1) * fraction,
+        );
+
+        Some((point, segment))
+    }
+}
+
+impl PolygonShape {
+    pub fn new(points: Vec<Point>) -> Self {
+        let outline = Outline::closed(&points);
+        let area: f32 = outline
+            .segments
+            .iter()
+            .map(|(start, end)| start.0 * end.1 - end.0 * start.1)
+            .sum();
+
+        let min_x = points.iter().map(|point| point.0).fold(f32::MAX, f32::min);
+        let max_x = points.iter().map(|point| point.0).fold(f32::MIN, f32::max);
+        let min_y = points.iter().map(|point| point.1).fold(f32::MAX, f32::min);
+        let max_y = points.iter().map(|point| point.1).fold(f32::MIN, f32::max);
+
+        Self {
+            points,
+            outline,
+            winding: area.signum(),
+            min: Point(min_x, min_y),
+            max: Point(max_x, max_y),
+        }
+    }
+
+    fn sample(&self, rng: &Rng) -> ShapePoint {
+        let Some((outline_point, _)) = self.outline.sample(rng) else {
+            return ShapePoint {
+                x: 0.,
+                y: 0.,
+                normal_radians: 0.,
+            };
+        };
+
+        // Thin polygons rarely get hit, those spawn on the outline instead.
+        let spawn = (0..MAX_POLYGON_ATTEMPTS)
+            .map(|_| {
+                Point(
+                    rng.gen_range(self.min.0, self.max.0),
+                    rng.gen_range(self.min.1, self.max.1),
+                )
+            })
+            .find(|point| polygon_contains(&self.points, point.0, point.1))
+            .unwrap_or(outline_point);
+
+        let distance = |(start, end): &&(Point, Point)| {
+            let closest = closest_point(start, end, spawn.0, spawn.1);
+            (closest.0 - spawn.0).powi(2) + (closest.1 - spawn.1).powi(2)
+        };
+
+        let (start, end) = self
+            .outline
+            .segments
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap();
+
+        ShapePoint {
+            x: spawn.0,
+            y: spawn.1,
+            normal_radians: edge_normal(start, end, self.winding),
+        }
+    }
+}
+
+fn radial_point(rng: &Rng, radius: f32) -> ShapePoint {
+    let angle = rng.gen_range(0., TAU);
+
+    ShapePoint {
+        x: radius * angle.cos(),
+        y: radius * angle.sin(),
+        normal_radians: angle,
+    }
+}
+
+/// Outward normal of the edge from start to end, winding is the sign of the polygon area.
+fn edge_normal(start: &Point, end: &Point, winding: f32) -> f32 {
+    (-(end.0 - start.0) * winding).atan2((end.1 - start.1) * winding)
+}
+
+fn segment_length((start, end): &(Point, Point)) -> f32 {
+    (end.0 - start.0).hypot(end.1 - start.1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::EmitterShape;
+    use crate::point::Point;
+    use crate::rng::Rng;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    fn assert_angle(expected: f32, actual: f32) {
+        let delta = (expected - actual).rem_euclid(2. * PI);
+        assert!(
+            delta.min(2. * PI - delta) < 0.001,
+            "{} != {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn samples_within_shapes() {
+        let rng = Rng::new(1);
+
+        for _ in 0..100 {
+            let point = EmitterShape::Ring {
+                inner_radius: 10.,
+                outer_radius: 20.,
+            }
+            .sample(&rng, 0., 0.);
+            let distance = point.x.hypot(point.y);
+            assert!((10. ..=20.).contains(&distance));
+            assert_angle(point.y.atan2(point.x), point.normal_radians);
+
+            let point = EmitterShape::Rectangle {
+                width: 40.,
+                height: 10.,
+            }
+            .sample(&rng, 0., 0.);
+            assert!(point.x.abs() <= 20. && point.y.abs() <= 5.);
+
+            // Close to the long edges, the normal faces up or down.
+            if 15. < 20. - point.x.abs() {
+                assert_angle(FRAC_PI_2 * point.y.signum(), point.normal_radians);
+            }
+        }
+    }
+
+    #[test]
+    fn faces_away_from_polygons() {
+        let rng = Rng::new(1);
+        let triangle = vec![Point(0., 0.), Point(0., 10.), Point(10., 0.)];
+        let reversed: Vec<Point> = triangle.iter().rev().copied().collect();
+
+        for points in [triangle, reversed] {
+            let shape = EmitterShape::polygon(points);
+
+            for _ in 0..100 {
+                let point = shape.sample(&rng, 0., 0.);
+                assert!(0. <= point.x && 0. <= point.y && point.x + point.y <= 10.);
+
+                // Normal of the closest edge: left, top or the diagonal.
+                let edges = [
+                    (point.x, PI),
+                    (point.y, -FRAC_PI_2),
+                    ((10. - point.x - point.y) / 2_f32.sqrt(), FRAC_PI_4),
+                ];
+                let (_, expected) = edges
+                    .into_iter()
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+                    .unwrap();
+                assert_angle(expected, point.normal_radians);
+            }
+        }
+
+        let polyline = EmitterShape::polyline(&[Point(0., 0.), Point(10., 0.), Point(10., 30.)]);
+
+        for _ in 0..100 {
+            let point = polyline.sample(&rng, 0., 0.);
+
+            if point.y == 0. {
+                assert_angle(-FRAC_PI_2, point.normal_radians);
+            } else {
+                assert_eq!(10., point.x);
+                assert_angle(0., point.normal_radians);
+            }
+        }
+    }
+
+    #[test]
+    fn spreads_over_thin_polygons() {
+        let rng = Rng::new(1);
+        let sliver =
+            EmitterShape::polygon(vec![Point(0., 0.), Point(100., 100.), Point(100., 100.5)]);
+        let mut corners = 0;
+
+        for _ in 0..100 {
+            let point = sliver.sample(&rng, 0., 0.);
+            assert!((point.x - point.y).abs() <= 0.5);
+
+            if point.x == 0. && point.y == 0. {
+                corners += 1;
+            }
+        }
+
+        assert!(corners < 5);
+    }
+}
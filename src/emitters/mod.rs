@@ -1,9 +1,12 @@
 pub mod diffusion_animation;
-pub mod emit_color_animation;
+pub mod emit_gradient_animation;
 pub mod emit_speed_animation;
 pub mod emitter;
 pub mod emitter_animation;
 pub mod emitter_animation_handler;
+pub mod emitter_shape;
 pub mod loose_movement_animation;
+pub mod path_animation;
 pub mod randomize_size_animation;
+pub mod sub_emitter;
 pub mod sway_animation;
@@ -0,0 +1,27 @@
+use super::emitter_animation::EmitterAnimate;
+use crate::easing::{tween, Easing};
+use crate::emitters::emitter_animation::EmitterData;
+use crate::path::Path;
+use crate::point::Point;
+use crate::rng::Rng;
+
+/// Moves the emitter over the path from from_ms until until_ms.
+pub struct PathAnimation {
+    pub from_ms: u32,
+    pub until_ms: u32,
+    pub path: Path,
+    pub easing: Easing,
+}
+
+impl EmitterAnimate for PathAnimation {
+    fn animate(&mut self, data: &mut EmitterData, cycle_ms: u32, _: &Rng) {
+        let Some(fraction) = tween(cycle_ms, self.from_ms, self.until_ms, self.easing) else {
+            return;
+        };
+
+        let Point(x, y) = self.path.point_at(fraction);
+
+        data.x = x;
+        data.y = y;
+    }
+}
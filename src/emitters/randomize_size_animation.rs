@@ -1,6 +1,6 @@
 use super::emitter_animation::EmitterAnimate;
 use crate::emitters::emitter_animation::EmitterData;
-use macroquad::prelude::rand;
+use crate::rng::Rng;
 
 pub struct RandomizeSizeAnimation {
     pub min_radius: f32,
@@ -8,7 +8,7 @@ pub struct RandomizeSizeAnimation {
 }
 
 impl EmitterAnimate for RandomizeSizeAnimation {
-    fn animate(&mut self, data: &mut EmitterData, _: u32) {
-        data.particle_radius = rand::gen_range(self.min_radius, self.max_radius);
+    fn animate(&mut self, data: &mut EmitterData, _: u32, rng: &Rng) {
+        data.particle_radius = rng.gen_range(self.min_radius, self.max_radius);
     }
 }
@@ -0,0 +1,81 @@
+use super::emitter::EmitterOptions;
+use crate::position::Position;
+use macroquad::prelude::Color;
+use std::fmt::Debug;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubEmitterTrigger {
+    /// When the particle spawns.
+    Birth,
+    /// When the particle reaches the end of its lifetime or gets killed by the boundaries.
+    Death,
+    /// Every time the particle collides with another particle.
+    Collision,
+}
+
+/// Spawns a new emitter at the position of a particle when the trigger happens.
+#[derive(Debug, Clone, Copy)]
+pub struct SubEmitter {
+    pub trigger: SubEmitterTrigger,
+    /// Builds the options of every spawned emitter, the emitter position is replaced by the
+    /// position of the particle.
+    pub options: fn() -> EmitterOptions,
+    /// Replaces the particle color of the spawned emitter with the color of the particle.
+    pub inherit_color: bool,
+    /// Fraction of the particle velocity added to every particle of the spawned emitter, 0 ignores
+    /// the velocity and 1 carries it over completely.
+    pub inherit_velocity: f32,
+}
+
+/// x and y are the center of the particle.
+pub struct SubEmitterData {
+    pub x: f32,
+    pub y: f32,
+    /// px/s
+    pub vx: f32,
+    /// px/s
+    pub vy: f32,
+    pub color: Color,
+}
+
+/// Emitter waiting to be added to the grid.
+pub struct SubEmission {
+    pub options: EmitterOptions,
+    /// px/s, added to the velocity of every emitted particle.
+    pub vx: f32,
+    /// px/s
+    pub vy: f32,
+}
+
+impl Debug for SubEmission {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SubEmission")
+    }
+}
+
+/// Adds an emission for every sub emitter with the trigger.
+pub fn trigger_sub_emitters(
+    sub_emitters: &[SubEmitter],
+    trigger: SubEmitterTrigger,
+    data: &SubEmitterData,
+    emissions: &mut Vec<SubEmission>,
+) {
+    for sub_emitter in sub_emitters.iter() {
+        if sub_emitter.trigger != trigger {
+            continue;
+        }
+
+        let mut options = (sub_emitter.options)();
+        options.emitter_position = Position::new(data.x, data.y);
+
+        if sub_emitter.inherit_color {
+            options.particle_color = data.color;
+        }
+
+        emissions.push(SubEmission {
+            options,
+            vx: data.vx * sub_emitter.inherit_velocity,
+            vy: data.vy * sub_emitter.inherit_velocity,
+        });
+    }
+}
@@ -2,7 +2,7 @@ use crate::force::force::{Force, ForceData};
 
 /**
  * Builds up applying force form 0 to nx/ny over time.
- * max_(vx/vy) will determin the max (positive or negative) speed a particle in similar direction needs to have the force applied.
+ * max_(vx/vy) will determin the max (positive or negative) speed (px/s) a particle in similar direction needs to have the force applied.
  */
 pub struct AcceleratingForce {
     pub nx: f32,
@@ -22,34 +22,31 @@ impl Force for AcceleratingForce {
         }
 
         let acceleration = ((force_cycle_ms - self.from_ms) as f32 / MS_PER_SEC).powf(2.);
-        let vx = self.nx * acceleration / data.mass;
-        let vy = self.ny * acceleration / data.mass;
+        let ax = self.nx * acceleration / data.mass;
+        let ay = self.ny * acceleration / data.mass;
 
-        let new_vx = data.vx + vx;
-        let new_vy = data.vy + vy;
-
-        if 0. < vx && 0. <= data.vx {
-            if new_vx <= self.max_vx {
-                data.vx += vx;
+        if 0. < ax && 0. <= data.vx {
+            if data.vx < self.max_vx {
+                data.ax += ax;
             }
-        } else if vx < 0. && data.vx <= 0. {
-            if self.max_vx <= new_vx {
-                data.vx += vx;
+        } else if ax < 0. && data.vx <= 0. {
+            if self.max_vx < data.vx {
+                data.ax += ax;
             }
         } else {
-            data.vx += vx;
+            data.ax += ax;
         }
 
-        if 0. < vy && 0. <= data.vy {
-            if new_vy <= self.max_vy {
-                data.vy += vy;
+        if 0. < ay && 0. <= data.vy {
+            if data.vy < self.max_vy {
+                data.ay += ay;
             }
-        } else if vy < 0. && data.vy <= 0. {
-            if self.max_vy <= new_vy {
-                data.vy += vy;
+        } else if ay < 0. && data.vy <= 0. {
+            if self.max_vy < data.vy {
+                data.ay += ay;
             }
         } else {
-            data.vy += vy;
+            data.ay += ay;
         }
     }
 }
@@ -1,50 +1,49 @@
 use crate::force::force::{Force, ForceData};
 
 pub struct ConstantForce {
+    /// In newton
     pub nx: f32,
+    /// In newton
     pub ny: f32,
+    /// px/s, the force is only applied while the particle is slower.
     pub max_vx: f32,
+    /// px/s, the force is only applied while the particle is slower.
     pub max_vy: f32,
     pub from_ms: u128,
     pub until_ms: u128,
 }
 
-const MS_PER_SEC: f32 = 1000.;
-
 impl Force for ConstantForce {
     fn apply(&self, data: &mut ForceData, force_cycle_ms: u128) {
         if force_cycle_ms < self.from_ms || self.until_ms <= force_cycle_ms {
             return;
         }
 
-        let vx = self.nx / data.mass;
-        let vy = self.ny / data.mass;
-
-        let new_vx = data.vx + vx;
-        let new_vy = data.vy + vy;
+        let ax = self.nx / data.mass;
+        let ay = self.ny / data.mass;
 
-        if 0. < vx && 0. <= data.vx {
-            if new_vx <= self.max_vx {
-                data.vx += vx;
+        if 0. < ax && 0. <= data.vx {
+            if data.vx < self.max_vx {
+                data.ax += ax;
             }
-        } else if vx < 0. && data.vx <= 0. {
-            if self.max_vx <= new_vx {
-                data.vx += vx;
+        } else if ax < 0. && data.vx <= 0. {
+            if self.max_vx < data.vx {
+                data.ax += ax;
             }
         } else {
-            data.vx += vx;
+            data.ax += ax;
         }
 
-        if 0. < vy && 0. <= data.vy {
-            if new_vy <= self.max_vy {
-                data.vy += vy;
+        if 0. < ay && 0. <= data.vy {
+            if data.vy < self.max_vy {
+                data.ay += ay;
             }
-        } else if vy < 0. && data.vy <= 0. {
-            if self.max_vy <= new_vy {
-                data.vy += vy;
+        } else if ay < 0. && data.vy <= 0. {
+            if self.max_vy < data.vy {
+                data.ay += ay;
             }
         } else {
-            data.vy += vy;
+            data.ay += ay;
         }
     }
 }
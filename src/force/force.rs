@@ -17,4 +17,7 @@ pub struct ForceData {
     pub vy: f32,
     pub radius: f32,
     pub mass: f32,
+    /// Acceleration in px/s², accumulated by the forces.
+    pub ax: f32,
+    pub ay: f32,
 }
@@ -1,4 +1,5 @@
 use crate::force::force::{Force, ForceData};
+use crate::path::Path;
 use crate::point::Point;
 
 pub struct GravitationalForce {
@@ -9,21 +10,14 @@ pub struct GravitationalForce {
     pub mass: f32,
     pub from_ms: u128,
     pub until_ms: u128,
-    pub start: Point,
-    pub end: Point,
+    /// Followed from from_ms until until_ms.
+    pub path: Path,
 }
 
 impl GravitationalForce {
     fn current_point(&self, force_cycle_ms: u128) -> Point {
-        let delta_current = force_cycle_ms - self.from_ms;
-        let delta_end = self.until_ms - self.from_ms;
-
-        let fraction = delta_current as f32 / delta_end as f32;
-
-        let x = self.start.0 + fraction * (self.end.0 - self.start.0);
-        let y = self.start.1 + fraction * (self.end.1 - self.start.1);
-
-        Point(x, y)
+        self.path
+            .point_at_ms(force_cycle_ms, self.from_ms, self.until_ms)
     }
 }
 
@@ -45,28 +39,20 @@ impl Force for GravitationalForce {
             return;
         }
 
-        let x_distance_pow = x_distance.powi(2);
-        let y_distance_pow = y_distance.powi(2);
-        let distance_pow = x_distance_pow + y_distance_pow;
+        let distance_pow = x_distance.powi(2) + y_distance.powi(2);
 
-        let top_formula = self.gravitation_force * self.mass * particle.mass;
-        let force = top_formula / distance_pow;
+        // On the point itself, there's no direction to pull in.
+        if distance_pow == 0. {
+            return;
+        }
 
-        let x_percentage = x_distance_pow / distance_pow;
-        let y_percentage = y_distance_pow / distance_pow;
+        let distance = distance_pow.sqrt();
 
-        let vx = force * x_percentage / particle.mass;
-        if 0. < x_distance {
-            particle.vx += vx;
-        } else if x_distance < 0. {
-            particle.vx -= vx;
-        }
+        let top_formula = self.gravitation_force * self.mass * particle.mass;
+        let force = top_formula / distance_pow;
 
-        let vy = force * y_percentage / particle.mass;
-        if 0. < y_distance {
-            particle.vy += vy;
-        } else if y_distance < 0. {
-            particle.vy -= vy;
-        }
+        // Unit direction towards the point, keeps the sign of the distances.
+        particle.ax += force * x_distance / distance / particle.mass;
+        particle.ay += force * y_distance / distance / particle.mass;
     }
 }
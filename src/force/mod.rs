@@ -1,3 +1,5 @@
+#![allow(clippy::module_inception)]
+
 pub mod accelerating_force;
 pub mod constant_force;
 pub mod force;
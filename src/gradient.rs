@@ -0,0 +1,280 @@
+use crate::easing::lerp;
+use macroquad::prelude::Color;
+
+/// Colour space in which the colours between two stops are mixed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorSpace {
+    /// Mixes the stored values directly, same as `lerp_color`.
+    Srgb,
+    /// Mixes light intensities, avoids the dark band between saturated colours.
+    LinearRgb,
+    /// Turns the hue along the shortest way around the colour wheel.
+    Hsv,
+    /// Perceptually uniform, keeps the brightness even over the gradient.
+    OkLab,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ColorStop {
+    /// Position on the gradient from 0 to 1.
+    pub offset: f32,
+    pub color: Color,
+}
+
+/// Colours at stops, mixed in the colour space between neighbouring stops. Alpha is always mixed linearly.
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    stops: Vec<ColorStop>,
+    color_space: ColorSpace,
+}
+
+impl Gradient {
+    /// Stops don't have to be sorted.
+    pub fn new(mut stops: Vec<ColorStop>, color_space: ColorSpace) -> Self {
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+
+        Self { stops, color_space }
+    }
+
+    /// Spreads the colours evenly from 0 to 1.
+    pub fn even(colors: Vec<Color>, color_space: ColorSpace) -> Self {
+        let last_index = (colors.len().max(2) - 1) as f32;
+        let stops = colors
+            .into_iter()
+            .enumerate()
+            .map(|(index, color)| ColorStop {
+                offset: index as f32 / last_index,
+                color,
+            })
+            .collect();
+
+        Self::new(stops, color_space)
+    }
+
+    /// Colour at fraction, the first and last stops extend to 0 and 1.
+    pub fn color_at(&self, fraction: f32) -> Color {
+        let index = self.stops.partition_point(|stop| stop.offset <= fraction);
+
+        if index == 0 {
+            return self
+                .stops
+                .first()
+                .map(|stop| stop.color)
+                .unwrap_or(Color::new(0., 0., 0., 0.));
+        }
+
+        if index == self.stops.len() {
+            return self.stops[index - 1].color;
+        }
+
+        let from = &self.stops[index - 1];
+        let to = &self.stops[index];
+        let between = (fraction - from.offset) / (to.offset - from.offset);
+
+        mix(from.color, to.color, between, self.color_space)
+    }
+}
+
+fn mix(from: Color, to: Color, fraction: f32, color_space: ColorSpace) -> Color {
+    let alpha = lerp(from.a, to.a, fraction);
+    let mix_3 = |a: [f32; 3], b: [f32; 3]| {
+        [
+            lerp(a[0], b[0], fraction),
+            lerp(a[1], b[1], fraction),
+            lerp(a[2], b[2], fraction),
+        ]
+    };
+
+    let [r, g, b] = match color_space {
+        ColorSpace::Srgb => mix_3([from.r, from.g, from.b], [to.r, to.g, to.b]),
+        ColorSpace::LinearRgb => {
+            let linear = mix_3(to_linear(from), to_linear(to));
+            linear.map(linear_to_srgb)
+        }
+        ColorSpace::Hsv => {
+            let [from_h, from_s, from_v] = to_hsv(from);
+            let [mut to_h, to_s, to_v] = to_hsv(to);
+
+            // Grey has no hue, so only the other colour decides it.
+            let from_h = if from_s == 0. { to_h } else { from_h };
+            if to_s == 0. {
+                to_h = from_h;
+            }
+
+            let mut delta_h = to_h - from_h;
+            if 180. < delta_h {
+                delta_h -= 360.;
+            } else if delta_h < -180. {
+                delta_h += 360.;
+            }
+
+            from_hsv([
+                (from_h + delta_h * fraction).rem_euclid(360.),
+                lerp(from_s, to_s, fraction),
+                lerp(from_v, to_v, fraction),
+            ])
+        }
+        ColorSpace::OkLab => {
+            let lab = mix_3(to_oklab(from), to_oklab(to));
+            from_oklab(lab).map(linear_to_srgb)
+        }
+    };
+
+    Color::new(r, g, b, alpha)
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0., 1.);
+
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+fn to_linear(color: Color) -> [f32; 3] {
+    [color.r, color.g, color.b].map(srgb_to_linear)
+}
+
+/// Hue in degrees, saturation and value from 0 to 1.
+fn to_hsv(color: Color) -> [f32; 3] {
+    let max = color.r.max(color.g).max(color.b);
+    let min = color.r.min(color.g).min(color.b);
+    let chroma = max - min;
+
+    let hue = if chroma == 0. {
+        0.
+    } else if max == color.r {
+        60. * ((color.g - color.b) / chroma).rem_euclid(6.)
+    } else if max == color.g {
+        60. * ((color.b - color.r) / chroma + 2.)
+    } else {
+        60. * ((color.r - color.g) / chroma + 4.)
+    };
+
+    let saturation = if max == 0. { 0. } else { chroma / max };
+
+    [hue, saturation, max]
+}
+
+fn from_hsv([hue, saturation, value]: [f32; 3]) -> [f32; 3] {
+    let chroma = value * saturation;
+    let x = chroma * (1. - ((hue / 60.).rem_euclid(2.) - 1.).abs());
+    let m = value - chroma;
+
+    let [r, g, b] = match (hue / 60.) as u32 {
+        0 => [chroma, x, 0.],
+        1 => [x, chroma, 0.],
+        2 => [0., chroma, x],
+        3 => [0., x, chroma],
+        4 => [x, 0., chroma],
+        _ => [chroma, 0., x],
+    };
+
+    [r + m, g + m, b + m]
+}
+
+/// https://bottosson.github.io/posts/oklab/
+fn to_oklab(color: Color) -> [f32; 3] {
+    let [r, g, b] = to_linear(color);
+
+    let l = (0.41222147 * r + 0.53633254 * g + 0.05144599 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+    let s = (0.08830246 * r + 0.28171884 * g + 0.6299787 * b).cbrt();
+
+    [
+        0.21045426 * l + 0.7936178 * m - 0.00407205 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.02590404 * l + 0.78277177 * m - 0.80867577 * s,
+    ]
+}
+
+/// Returns linear rgb.
+fn from_oklab([lightness, a, b]: [f32; 3]) -> [f32; 3] {
+    let l = (lightness + 0.39633778 * a + 0.21580376 * b).powi(3);
+    let m = (lightness - 0.10556135 * a - 0.06385417 * b).powi(3);
+    let s = (lightness - 0.08948418 * a - 1.2914855 * b).powi(3);
+
+    [
+        4.0767417 * l - 3.3077116 * m + 0.23096993 * s,
+        -1.268438 * l + 2.6097574 * m - 0.3413194 * s,
+        -0.00419609 * l - 0.7034186 * m + 1.7076147 * s,
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ColorSpace, ColorStop, Gradient};
+    use macroquad::prelude::Color;
+
+    const SPACES: [ColorSpace; 4] = [
+        ColorSpace::Srgb,
+        ColorSpace::LinearRgb,
+        ColorSpace::Hsv,
+        ColorSpace::OkLab,
+    ];
+
+    fn assert_color(expected: Color, actual: Color) {
+        let delta = (expected.r - actual.r).abs()
+            + (expected.g - actual.g).abs()
+            + (expected.b - actual.b).abs()
+            + (expected.a - actual.a).abs();
+
+        assert!(delta < 0.01, "{:?} != {:?}", expected, actual);
+    }
+
+    #[test]
+    fn hits_every_stop() {
+        let red = Color::new(1., 0., 0., 1.);
+        let yellow = Color::new(1., 1., 0., 1.);
+        let smoke = Color::new(0.2, 0.2, 0.2, 0.);
+
+        for color_space in SPACES {
+            let gradient = Gradient::new(
+                vec![
+                    ColorStop {
+                        offset: 1.,
+                        color: smoke,
+                    },
+                    ColorStop {
+                        offset: 0.,
+                        color: yellow,
+                    },
+                    ColorStop {
+                        offset: 0.3,
+                        color: red,
+                    },
+                ],
+                color_space,
+            );
+
+            assert_color(yellow, gradient.color_at(-1.));
+            assert_color(yellow, gradient.color_at(0.));
+            assert_color(red, gradient.color_at(0.3));
+            assert_color(smoke, gradient.color_at(1.));
+            assert_color(smoke, gradient.color_at(2.));
+        }
+    }
+
+    #[test]
+    fn mixes_in_color_space() {
+        let red = Color::new(1., 0., 0., 1.);
+        let blue = Color::new(0., 0., 1., 1.);
+        let mix = |color_space| Gradient::even(vec![red, blue], color_space).color_at(0.5);
+
+        assert_color(Color::new(0.5, 0., 0.5, 1.), mix(ColorSpace::Srgb));
+        assert_color(Color::new(0.735, 0., 0.735, 1.), mix(ColorSpace::LinearRgb));
+        // Shortest way around the wheel passes magenta.
+        assert_color(Color::new(1., 0., 1., 1.), mix(ColorSpace::Hsv));
+        assert_color(Color::new(0.55, 0.326, 0.637, 1.), mix(ColorSpace::OkLab));
+    }
+}
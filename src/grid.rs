@@ -1,27 +1,67 @@
+use crate::boundary::{Boundaries, BoundaryData, BoundaryMode};
+use crate::clock::Clock;
+use crate::collision::{resolve_collision, time_of_impact};
+use crate::container::Container;
 use crate::emitters::emitter::Emitter;
 use crate::emitters::emitter::EmitterOptions;
+use crate::emitters::sub_emitter::{trigger_sub_emitters, SubEmission, SubEmitterTrigger};
 use crate::force::force::ForceData;
 use crate::force::force_handler::ForceHandler;
-use std::{rc::Rc, time::Instant};
+use crate::integrators::integrator::{IntegrationData, Integrator};
+use crate::obstacles::obstacle::Obstacle;
+use crate::rng::Rng;
+use crate::slot_map::SlotMap;
+use crate::swarm_emitter::{SwarmEmitter, SwarmEmitterOptions};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use crate::{
-    collision::CollisionData,
     fill_style::FillStyle,
     particle::{Particle, ParticleAttributes},
+    particle_handle::{ParticleHandle, ParticleLocation, ParticleRef},
+    point::Point,
     position::Position,
+    renderer::Renderer,
+    substeps::Substeps,
 };
 use macroquad::prelude::*;
 
+/// Gap kept to the right and bottom walls, a particle touching them counts as outside of the grid.
+pub const WALL_MARGIN: f32 = 0.1;
+
+/**
+ * Particles are stored in a uniform spatial hash over the whole grid area, every spot is a square
+ * of possibility_side_length. Collisions are checked against the 3x3 spots around a particle,
+ * so possibility_side_length needs to be at least the diameter of the largest particle.
+ */
 #[derive(Debug)]
 pub struct Grid {
     pub possibility_spots: Vec<Vec<Particle>>,
+    /// Emitter and particle index of the emitted particles that collide with the grid, bucketed
+    /// like possibility_spots. Rebuilt every step.
+    emitted_spots: Vec<Vec<(usize, usize)>>,
+    /// Spots holding particles pushed around by collisions, re-bucketed after the update.
+    moved_spots: Vec<usize>,
+    /// Spot and index within the spot of every grid particle.
+    particle_slots: SlotMap<(usize, usize)>,
+    /// Emitters spawned by sub emitters, added at the end of the update that triggered them.
+    sub_emissions: Vec<SubEmission>,
+    /// Index in emitters of every emitter.
+    emitter_slots: SlotMap<usize>,
+    /// Spots on the x axis over the whole grid.
+    pub possibility_columns: usize,
+    /// Spots on the y axis over the whole grid.
+    pub possibility_rows: usize,
     pub cell_x_count: usize,
     pub cell_y_count: usize,
     pub possibility_x_count: usize,
     pub possibility_y_count: usize,
     pub possibility_side_length: usize,
     pub position: Position,
+    /// Physics updates, a step can run several with a fixed timestep.
     pub frame: u64,
+    /// Steps, one per rendered frame.
+    pub render_frame: u64,
     pub cell_width: usize,
     pub cell_height: usize,
     pub duration: u128,
@@ -30,7 +70,20 @@ pub struct Grid {
     pub emitted_particle_count: u32,
     pub force_handler: Option<ForceHandler>,
     pub emitters: Vec<Emitter>,
-    pub lifetime: Instant,
+    pub swarm_emitters: Vec<SwarmEmitter>,
+    pub obstacles: Vec<Box<dyn Obstacle>>,
+    pub clock: Box<dyn Clock>,
+    pub rng: Rc<Rng>,
+    /// Simulated time, lags behind the clock with at most one fixed timestep.
+    pub time: Duration,
+    clock_time: Duration,
+    fixed_timestep: Option<Duration>,
+    accumulator: Duration,
+    pub integrator: Rc<dyn Integrator>,
+    substeps: Substeps,
+    pub boundaries: Boundaries,
+    /// Total amount of substeps so far.
+    pub substep: u64,
 }
 
 pub struct GridOptions {
@@ -41,16 +94,29 @@ pub struct GridOptions {
     pub possibility_side_length: usize,
     pub position: Position,
     pub force_handler: Option<ForceHandler>,
+    pub clock: Box<dyn Clock>,
+    /// Seeds the random number generator, equal seeds result in equal simulations.
+    pub seed: u64,
+    /// Advance physics in steps of exactly this duration, independent of the frame rate.
+    /// Steps with the frame time if None.
+    pub fixed_timestep: Option<Duration>,
+    /// Also used by emitters without their own integrator.
+    pub integrator: Rc<dyn Integrator>,
+    pub substeps: Substeps,
+    pub boundaries: Boundaries,
 }
 
+/// Prevents a spiral of death when the physics can't keep up with the clock.
+const MAX_FIXED_STEPS: u32 = 8;
+
 fn create_possibility_grid(
-    possibility_x_count: usize,
-    possiblity_y_count: usize,
+    possibility_columns: usize,
+    possibility_rows: usize,
 ) -> Vec<Vec<Particle>> {
     let mut spots: Vec<Vec<Particle>> = Vec::new();
 
-    for _ in 0..possibility_x_count {
-        for _ in 0..possiblity_y_count {
+    for _ in 0..possibility_columns {
+        for _ in 0..possibility_rows {
             spots.push(Vec::new());
         }
     }
@@ -68,15 +134,28 @@ impl Grid {
             possibility_side_length,
             mut position,
             force_handler,
+            clock,
+            seed,
+            fixed_timestep,
+            integrator,
+            substeps,
+            boundaries,
         } = options;
         let cell_width = possibility_x_count * possibility_side_length;
         let cell_height = possibility_y_count * possibility_side_length;
         position.width = (cell_x_count * cell_width) as f32;
         position.height = (cell_y_count * cell_height) as f32;
 
-        let possibility_spots = create_possibility_grid(possibility_x_count, possibility_y_count);
+        let possibility_columns = cell_x_count * possibility_x_count;
+        let possibility_rows = cell_y_count * possibility_y_count;
+        let possibility_spots = create_possibility_grid(possibility_columns, possibility_rows);
 
         Self {
+            emitted_spots: vec![Vec::new(); possibility_spots.len()],
+            moved_spots: Vec::new(),
+            particle_slots: SlotMap::default(),
+            sub_emissions: Vec::new(),
+            emitter_slots: SlotMap::default(),
             cell_x_count,
             cell_y_count,
             possibility_x_count,
@@ -84,16 +163,30 @@ impl Grid {
             possibility_side_length,
             position,
             possibility_spots,
+            possibility_columns,
+            possibility_rows,
             cell_width,
             cell_height,
             frame: 0,
+            render_frame: 0,
             duration: 0,
             fps: 0,
             particle_count: 0,
             emitted_particle_count: 0,
             force_handler,
             emitters: Vec::new(),
-            lifetime: Instant::now(),
+            swarm_emitters: Vec::new(),
+            obstacles: Vec::new(),
+            time: clock.elapsed(),
+            clock_time: clock.elapsed(),
+            clock,
+            rng: Rc::new(Rng::new(seed)),
+            fixed_timestep,
+            accumulator: Duration::ZERO,
+            integrator,
+            substeps,
+            boundaries,
+            substep: 0,
         }
     }
 
@@ -101,34 +194,37 @@ impl Grid {
         for v_index in 0..self.possibility_spots.len() {
             println!(
                 "possiblity x: {}, y: {}, has {} particles.",
-                v_index % self.possibility_x_count,
-                v_index / self.possibility_x_count,
+                v_index % self.possibility_columns,
+                v_index / self.possibility_columns,
                 self.possibility_spots[v_index].len()
             );
         }
         println!("------------------");
     }
 
-    fn cell_x_index(&self, x_coord: f32) -> usize {
-        x_coord as usize / self.cell_width
-    }
-
-    fn cell_y_index(&self, y_coord: f32) -> usize {
-        y_coord as usize / self.cell_height
-    }
-
+    /// Coordinates outside of the grid are clamped to the closest spot.
     fn possibility_x_index(&self, x_coord: f32) -> usize {
-        let x_residual = x_coord as usize % self.cell_width;
-        x_residual / self.possibility_side_length
+        let x_index = x_coord.max(0.) as usize / self.possibility_side_length;
+        x_index.min(self.possibility_columns - 1)
     }
 
+    /// Coordinates outside of the grid are clamped to the closest spot.
     fn possibility_y_index(&self, y_coord: f32) -> usize {
-        let y_residual = y_coord as usize % self.cell_height;
-        y_residual / self.possibility_side_length
+        let y_index = y_coord.max(0.) as usize / self.possibility_side_length;
+        y_index.min(self.possibility_rows - 1)
     }
 
     fn possibility_index(&self, x_index: usize, y_index: usize) -> usize {
-        self.possibility_x_count * y_index + x_index
+        self.possibility_columns * y_index + x_index
+    }
+
+    /// Indices of the 3x3 spots around (and including) the spot of x_index, y_index.
+    fn neighbour_spots(&self, x_index: usize, y_index: usize) -> impl Iterator<Item = usize> {
+        let columns = self.possibility_columns;
+        let x_range = x_index.saturating_sub(1)..=(x_index + 1).min(columns - 1);
+        let y_range = y_index.saturating_sub(1)..=(y_index + 1).min(self.possibility_rows - 1);
+
+        y_range.flat_map(move |y| x_range.clone().map(move |x| columns * y + x))
     }
 
     fn vec_spot_particle(&self, particle: &Particle) -> usize {
@@ -138,192 +234,534 @@ impl Grid {
         self.possibility_index(new_x_spot, new_y_spot)
     }
 
-    fn has_collision(
-        &mut self,
-        particle: &mut Particle,
-        data: &mut CollisionData,
-        vec_index: usize,
-    ) -> bool {
-        for other in self.possibility_spots[vec_index].iter_mut() {
-            if particle.handle_possible_collision(other, data) {
-                return true;
-            }
-        }
-        false
-    }
-
-    fn handle_collision(&mut self, particle: &mut Particle, data: &mut CollisionData) -> usize {
-        let CollisionData {
-            new_x,
-            new_y,
-            end_new_x,
-            end_new_y,
-        } = *data;
+    /// Takes the particle out of its spot, the last particle of the spot takes its place.
+    fn take_particle(&mut self, vec_index: usize, spot_index: usize) -> Particle {
+        let spot = &mut self.possibility_spots[vec_index];
+        let particle = spot.swap_remove(spot_index);
 
-        let new_x_spot = self.possibility_x_index(new_x);
-        let new_y_spot = self.possibility_y_index(new_y);
-
-        let new_vec_index = self.possibility_index(new_x_spot, new_y_spot);
-
-        if self.has_collision(particle, data, new_vec_index) {
-            return self.vec_spot_particle(particle);
+        if let Some(moved) = spot.get(spot_index) {
+            self.particle_slots.set(moved.id, (vec_index, spot_index));
         }
 
-        let end_x_spot = self.possibility_x_index(end_new_x);
-        let end_y_spot = self.possibility_y_index(end_new_y);
+        particle
+    }
 
-        let has_diff_end_x_spot = end_x_spot != new_x_spot;
-        let has_diff_end_y_spot = end_y_spot != new_y_spot;
+    fn put_particle(&mut self, vec_index: usize, particle: Particle) {
+        let spot = &mut self.possibility_spots[vec_index];
+        self.particle_slots
+            .set(particle.id, (vec_index, spot.len()));
+        spot.push(particle);
+    }
 
-        if has_diff_end_x_spot {
-            let new_vec_index = self.possibility_index(end_x_spot, new_y_spot);
+    /// Drops a particle that was taken out of its spot.
+    fn kill_particle(&mut self, particle: Particle) {
+        self.particle_slots.remove(particle.id);
+        self.particle_count -= 1;
+    }
 
-            if self.has_collision(particle, data, new_vec_index) {
-                return self.vec_spot_particle(particle);
+    /// Resolves collisions with every particle in the neighbouring spots.
+    fn handle_collision(&mut self, particle: &mut Particle) {
+        let x_spot = self.possibility_x_index(particle.x);
+        let y_spot = self.possibility_y_index(particle.y);
+
+        for vec_index in self.neighbour_spots(x_spot, y_spot) {
+            for other in self.possibility_spots[vec_index].iter_mut() {
+                if particle.handle_possible_collision(other) {
+                    self.moved_spots.push(vec_index);
+
+                    let emissions = &mut self.sub_emissions;
+                    let collision = SubEmitterTrigger::Collision;
+                    let data = particle.sub_emitter_data();
+                    trigger_sub_emitters(&particle.sub_emitters, collision, &data, emissions);
+                    let data = other.sub_emitter_data();
+                    trigger_sub_emitters(&other.sub_emitters, collision, &data, emissions);
+                }
             }
         }
+    }
 
-        if has_diff_end_y_spot {
-            let new_vec_index = self.possibility_index(new_x_spot, end_y_spot);
+    /// Triggers the sub emitters of a grid particle.
+    fn trigger(&mut self, trigger: SubEmitterTrigger, particle: &Particle) {
+        let data = particle.sub_emitter_data();
+        trigger_sub_emitters(
+            &particle.sub_emitters,
+            trigger,
+            &data,
+            &mut self.sub_emissions,
+        );
+    }
 
-            if self.has_collision(particle, data, new_vec_index) {
-                return self.vec_spot_particle(particle);
+    /**
+     * Adds the emitters spawned by sub emitters and lets them emit right away, so a burst shows up
+     * in the frame that triggered it. Emissions triggered by the new emitters wait for the next
+     * update, which keeps chains of sub emitters from spawning endlessly within one update.
+     */
+    fn add_sub_emissions(&mut self) {
+        for emission in std::mem::take(&mut self.sub_emissions) {
+            self.add_emitter(emission.options);
+
+            if let Some(emitter) = self.emitters.last_mut() {
+                emitter.inherit_velocity(emission.vx, emission.vy);
+                emitter.step(self.time, 0., &self.obstacles);
+                self.sub_emissions.extend(emitter.take_sub_emissions());
             }
         }
+    }
 
-        if has_diff_end_x_spot && has_diff_end_y_spot {
-            let new_vec_index = self.possibility_index(end_x_spot, end_y_spot);
+    fn update_spot(&mut self, vec_index: usize, spot_index: usize, dt: f32) {
+        let mut particle = self.take_particle(vec_index, spot_index);
 
-            if self.has_collision(particle, data, new_vec_index) {
-                return self.vec_spot_particle(particle);
-            }
+        if particle.has_expired(self.time) {
+            self.trigger(SubEmitterTrigger::Death, &particle);
+            self.kill_particle(particle);
+            return;
         }
 
-        new_vec_index
-    }
-
-    fn update_spot(&mut self, vec_index: usize, spot_index: usize, elapsed_ms: u128) {
-        let mut particle = self.possibility_spots[vec_index].swap_remove(spot_index);
-
-        let mut data = ForceData {
+        let mut data = IntegrationData {
             x: particle.x,
             y: particle.y,
             vx: particle.vx,
             vy: particle.vy,
-            radius: particle.radius,
-            mass: particle.mass,
         };
 
-        if let Some(force_handler) = &mut self.force_handler {
-            force_handler.apply(&mut data, elapsed_ms);
-            particle.vx = data.vx;
-            particle.vy = data.vy;
-        }
+        let force_handler = &self.force_handler;
+        let force_cycle_ms = self.time.as_millis();
+
+        self.integrator.integrate(&mut data, dt, &mut |state| {
+            let mut force_data = ForceData {
+                x: state.x,
+                y: state.y,
+                vx: state.vx,
+                vy: state.vy,
+                radius: particle.radius,
+                mass: particle.mass,
+                ax: 0.,
+                ay: 0.,
+            };
+
+            if let Some(force_handler) = force_handler {
+                force_handler.apply(&mut force_data, force_cycle_ms);
+            }
+
+            (force_data.ax, force_data.ay)
+        });
 
-        let new_vec_index = self.update_particle(&mut particle);
+        let Some(new_vec_index) = self.update_particle(&mut particle, &data, dt) else {
+            self.trigger(SubEmitterTrigger::Death, &particle);
+            self.kill_particle(particle);
+            return;
+        };
 
         if new_vec_index != vec_index {
-            particle.queue_frame = self.frame;
+            particle.queue_substep = self.substep;
         }
 
-        self.possibility_spots[new_vec_index].push(particle);
+        self.put_particle(new_vec_index, particle);
     }
 
     /**
-     * returns true if index needs to be incremented
+     * returns the index of the spot the particle belongs to after the update, None if it got
+     * removed by the boundaries.
      */
-    fn update_particle(&mut self, particle: &mut Particle) -> usize {
-        let new_x = particle.x + particle.vx;
-        let new_y = particle.y + particle.vy;
-        let end_new_x = new_x + particle.diameter;
-        let end_new_y = new_y + particle.diameter;
+    fn update_particle(
+        &mut self,
+        particle: &mut Particle,
+        integrated: &IntegrationData,
+        dt: f32,
+    ) -> Option<usize> {
+        let mut new_x = integrated.x;
+        let mut new_y = integrated.y;
+        particle.vx = integrated.vx;
+        particle.vy = integrated.vy;
+
+        if particle.continuous_collision {
+            (new_x, new_y) = self.sweep(particle, new_x, new_y);
+        }
 
-        let x_out_of_bounds = new_x < 0. || self.position.width <= end_new_x;
-        let y_out_of_bounds = new_y < 0. || self.position.height <= end_new_y;
+        let mut boundary_data = BoundaryData {
+            x: new_x,
+            y: new_y,
+            vx: particle.vx,
+            vy: particle.vy,
+            elasticity: particle.elasticity,
+        };
 
-        // Inverse direction.
-        let elasticity_force = -1. * particle.elasticity;
+        let max_x = self.position.width - WALL_MARGIN - particle.diameter;
+        let max_y = self.position.height - WALL_MARGIN - particle.diameter;
 
-        if x_out_of_bounds {
-            particle.vx *= elasticity_force;
+        if !self.boundaries.apply(&mut boundary_data, max_x, max_y, dt) {
+            // Death sub emitters spawn where the particle left the grid.
+            particle.x = new_x;
+            particle.y = new_y;
+            return None;
         }
 
-        if y_out_of_bounds {
-            particle.vy *= elasticity_force;
+        new_x = boundary_data.x;
+        new_y = boundary_data.y;
+        particle.vx = boundary_data.vx;
+        particle.vy = boundary_data.vy;
+
+        // Passed an open edge, grid particles can't live outside of the grid.
+        if new_x < 0. || max_x < new_x || new_y < 0. || max_y < new_y {
+            particle.x = new_x;
+            particle.y = new_y;
+            return None;
         }
 
-        particle.apply_friction();
-        particle.animate();
+        particle.apply_friction(dt);
+        particle.animate(self.time, &self.rng);
+        particle.update_trail(self.time);
+        particle.transform(new_x, new_y, self.position.width, self.position.height);
 
-        let mut data = CollisionData {
-            new_x,
-            new_y,
-            end_new_x,
-            end_new_y,
-        };
+        for obstacle in self.obstacles.iter() {
+            particle.handle_obstacle(obstacle.as_ref(), dt);
+        }
+
+        self.handle_collision(particle);
 
-        let new_vec_index = self.handle_collision(particle, &mut data);
+        // Positional correction can push the particle out of the grid.
+        particle.transform(
+            particle.x,
+            particle.y,
+            self.position.width,
+            self.position.height,
+        );
+        Some(self.vec_spot_particle(particle))
+    }
+
+    /**
+     * Moves the particle from its current position towards end_x, end_y and stops at the first
+     * particle or wall it hits, bouncing off of it. Returns the position the particle ends up at.
+     */
+    fn sweep(&mut self, particle: &mut Particle, end_x: f32, end_y: f32) -> (f32, f32) {
+        let dx = end_x - particle.x;
+        let dy = end_y - particle.y;
+
+        let max_x = self.position.width - WALL_MARGIN - particle.diameter;
+        let max_y = self.position.height - WALL_MARGIN - particle.diameter;
+        let boundaries = &self.boundaries;
+        let wall_x = wall_impact(particle.x, dx, max_x, boundaries.left, boundaries.right);
+        let wall_y = wall_impact(particle.y, dy, max_y, boundaries.top, boundaries.bottom);
+        let wall = wall_x.min(wall_y);
+
+        let data = particle.collision_data();
+        let mut impact: Option<(f32, usize, usize)> = None;
+
+        let min_x = particle.x.min(end_x);
+        let min_y = particle.y.min(end_y);
+        let max_x = particle.x.max(end_x) + particle.diameter;
+        let max_y = particle.y.max(end_y) + particle.diameter;
+
+        for vec_index in self.spots_in_area(min_x, min_y, max_x, max_y) {
+            for (spot_index, other) in self.possibility_spots[vec_index].iter().enumerate() {
+                let fraction = time_of_impact(&data, dx, dy, &other.collision_data());
+
+                if let Some(fraction) = fraction {
+                    if impact.is_none_or(|(closest, _, _)| fraction < closest) {
+                        impact = Some((fraction, vec_index, spot_index));
+                    }
+                }
+            }
+        }
+
+        match impact {
+            Some((fraction, vec_index, spot_index)) if fraction <= wall => {
+                particle.x += dx * fraction;
+                particle.y += dy * fraction;
+                particle.handle_impact(&mut self.possibility_spots[vec_index][spot_index]);
+                (particle.x, particle.y)
+            }
+            _ if wall <= 1. => {
+                // Inverse direction.
+                if wall_x == wall {
+                    particle.vx *= -particle.elasticity;
+                }
 
-        particle.transform(self.position.width, self.position.height);
-        particle.draw(&self.position);
+                if wall_y == wall {
+                    particle.vy *= -particle.elasticity;
+                }
 
-        new_vec_index
+                (particle.x + dx * wall, particle.y + dy * wall)
+            }
+            _ => (end_x, end_y),
+        }
     }
 
     pub fn fill(&mut self, attributes: &ParticleAttributes, count: u32, fill_style: FillStyle) {
-        self.particle_count += count;
-
         match fill_style {
             FillStyle::WhiteNoise => self.fill_white_noise(attributes, count),
         }
     }
 
+    pub fn add_swarm_emitter(&mut self, options: SwarmEmitterOptions) {
+        self.swarm_emitters.push(SwarmEmitter::new(
+            self.position,
+            options,
+            self.time,
+            Rc::clone(&self.rng),
+            Rc::clone(&self.integrator),
+        ));
+    }
+
+    pub fn add_obstacle(&mut self, obstacle: Box<dyn Obstacle>) {
+        self.obstacles.push(obstacle);
+    }
+
     pub fn add_emitter(&mut self, options: EmitterOptions) {
-        self.emitters.push(Emitter::new(self.position, options));
+        let id = self.emitter_slots.insert(self.emitters.len());
+
+        self.emitters.push(Emitter::new(
+            id,
+            self.position,
+            options,
+            self.time,
+            Rc::clone(&self.rng),
+            Rc::clone(&self.integrator),
+        ));
     }
 
-    pub fn draw(&mut self) {
-        let update_gui = self.frame % 50 == 0;
+    /// Advances the simulation up to the current clock time without drawing anything.
+    pub fn step(&mut self) {
+        let update_gui = self.render_frame.is_multiple_of(50);
+        let loop_start = Instant::now();
+
+        self.clock.tick();
+        let now = self.clock.elapsed();
+        let frame_time = now.saturating_sub(self.clock_time);
+        self.clock_time = now;
+
+        if let Some(timestep) = self.fixed_timestep {
+            self.accumulator += frame_time;
+
+            let mut steps = 0;
+            while timestep <= self.accumulator {
+                if steps == MAX_FIXED_STEPS {
+                    self.accumulator = Duration::ZERO;
+                    break;
+                }
+
+                self.accumulator -= timestep;
+                self.update(timestep);
+                steps += 1;
+            }
+        } else {
+            self.update(frame_time);
+        }
+
         if update_gui {
-            self.duration = self.lifetime.elapsed().as_micros();
+            self.duration = loop_start.elapsed().as_micros();
+            self.count_emitted_particles();
+        }
+
+        self.render_frame += 1;
+    }
+
+    fn count_emitted_particles(&mut self) {
+        self.emitted_particle_count = self
+            .emitters
+            .iter()
+            .fold(0, |acc, emitter| acc + emitter.particle_count);
+    }
+
+    fn substep_count(&self, dt: f32) -> u32 {
+        match self.substeps {
+            Substeps::Fixed(count) => count.max(1),
+            Substeps::Adaptive {
+                max_distance,
+                max_substeps,
+            } => {
+                let max_speed = self
+                    .possibility_spots
+                    .iter()
+                    .flatten()
+                    .fold(0_f32, |acc, p| acc.max(p.vx.hypot(p.vy)));
+
+                let count = (max_speed * dt / max_distance).ceil() as u32;
+                count.clamp(1, max_substeps.max(1))
+            }
+        }
+    }
+
+    fn update(&mut self, dt: Duration) {
+        let start = self.time;
+        let substeps = self.substep_count(dt.as_secs_f32());
+
+        for substep in 1..=substeps {
+            let previous = self.time;
+            self.time = start + dt * substep / substeps;
+            self.substep += 1;
+
+            let substep_dt = (self.time - previous).as_secs_f32();
+            self.update_spots(substep_dt);
+            self.rebucket_moved_spots();
+        }
+
+        let dt = dt.as_secs_f32();
+
+        for emitter in self.emitters.iter_mut() {
+            emitter.step(self.time, dt, &self.obstacles);
+            self.sub_emissions.extend(emitter.take_sub_emissions());
+        }
+
+        for emitter in self.emitters.iter().filter(|emitter| emitter.delete) {
+            self.emitter_slots.remove(emitter.id);
         }
 
-        let elapsed_ms = self.lifetime.elapsed().as_millis();
+        self.emitters.retain(|emitter| !emitter.delete);
 
+        for (index, emitter) in self.emitters.iter().enumerate() {
+            self.emitter_slots.set(emitter.id, index);
+        }
+
+        for swarm_emitter in self.swarm_emitters.iter_mut() {
+            swarm_emitter.step(self.time, dt);
+        }
+
+        self.swarm_emitters
+            .retain(|swarm_emitter| !swarm_emitter.delete);
+        self.collide_emitted_particles();
+        self.add_sub_emissions();
+
+        self.frame += 1;
+    }
+
+    fn collide_emitted_particles(&mut self) {
+        let mut emitted_spots = std::mem::take(&mut self.emitted_spots);
+
+        for spot in emitted_spots.iter_mut() {
+            spot.clear();
+        }
+
+        for (emitter_index, emitter) in self.emitters.iter().enumerate() {
+            if !emitter.collides_with_grid() {
+                continue;
+            }
+
+            for (particle_index, particle) in emitter.particles().iter().enumerate() {
+                // Bucketed by the top left corner, same as grid particles.
+                let x_index = self.possibility_x_index(particle.x - particle.radius);
+                let y_index = self.possibility_y_index(particle.y - particle.radius);
+                let vec_index = self.possibility_index(x_index, y_index);
+
+                emitted_spots[vec_index].push((emitter_index, particle_index));
+            }
+        }
+
+        for vec_index in 0..emitted_spots.len() {
+            let x_index = vec_index % self.possibility_columns;
+            let y_index = vec_index / self.possibility_columns;
+
+            for &emitted in emitted_spots[vec_index].iter() {
+                for other_index in self.neighbour_spots(x_index, y_index) {
+                    let (emitter_index, particle_index) = emitted;
+                    let emitter = &mut self.emitters[emitter_index];
+                    let particle = &mut emitter.particles_mut()[particle_index];
+                    let mut collided = false;
+
+                    for other in self.possibility_spots[other_index].iter_mut() {
+                        if particle.handle_possible_collision(other) {
+                            self.moved_spots.push(other_index);
+                            collided = true;
+
+                            let data = other.sub_emitter_data();
+                            let collision = SubEmitterTrigger::Collision;
+                            let emissions = &mut self.sub_emissions;
+                            trigger_sub_emitters(&other.sub_emitters, collision, &data, emissions);
+                        }
+                    }
+
+                    if collided {
+                        emitter.handle_particle_collision(particle_index);
+                    }
+
+                    for &other in emitted_spots[other_index].iter() {
+                        // Every pair only once.
+                        if emitted < other {
+                            self.collide_emitted_pair(emitted, other);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.emitted_spots = emitted_spots;
+
+        self.rebucket_moved_spots();
+    }
+
+    /// Positional correction can push grid particles out of the grid or their spot.
+    fn rebucket_moved_spots(&mut self) {
+        let mut moved_spots = std::mem::take(&mut self.moved_spots);
+        moved_spots.sort_unstable();
+        moved_spots.dedup();
+
+        for &vec_index in moved_spots.iter() {
+            for spot_index in (0..self.possibility_spots[vec_index].len()).rev() {
+                let mut particle = self.take_particle(vec_index, spot_index);
+                particle.transform(
+                    particle.x,
+                    particle.y,
+                    self.position.width,
+                    self.position.height,
+                );
+
+                let new_vec_index = self.vec_spot_particle(&particle);
+                self.put_particle(new_vec_index, particle);
+            }
+        }
+
+        // Keeps the allocation for the next update.
+        moved_spots.clear();
+        self.moved_spots = moved_spots;
+    }
+
+    fn collide_emitted_pair(&mut self, a: (usize, usize), b: (usize, usize)) {
+        let mut data = self.emitters[a.0].particles()[a.1].collision_data();
+        let mut other_data = self.emitters[b.0].particles()[b.1].collision_data();
+
+        if resolve_collision(&mut data, &mut other_data) {
+            self.emitters[a.0].particles_mut()[a.1].apply_collision_data(&data);
+            self.emitters[b.0].particles_mut()[b.1].apply_collision_data(&other_data);
+            self.emitters[a.0].handle_particle_collision(a.1);
+            self.emitters[b.0].handle_particle_collision(b.1);
+        }
+    }
+
+    fn update_spots(&mut self, dt: f32) {
         for vec_index in 0..self.possibility_spots.len() {
             for spot_index in (0..self.possibility_spots[vec_index].len()).rev() {
-                if self.possibility_spots[vec_index][spot_index].queue_frame == self.frame {
+                if self.possibility_spots[vec_index][spot_index].queue_substep == self.substep {
                     continue;
                 }
 
-                self.update_spot(vec_index, spot_index, elapsed_ms);
+                self.update_spot(vec_index, spot_index, dt);
             }
         }
+    }
 
-        for i in (0..self.emitters.len()).rev() {
-            let mut emitter = self.emitters.swap_remove(i);
-            emitter.emit();
-
-            if !emitter.delete {
-                self.emitters.push(emitter);
-            }
+    pub fn draw(&self, renderer: &mut dyn Renderer) {
+        for obstacle in self.obstacles.iter() {
+            obstacle.draw(renderer, &self.position);
         }
 
-        self.frame += 1;
+        for particle in self.possibility_spots.iter().flatten() {
+            particle.draw(renderer, &self.position);
+        }
 
-        if update_gui {
-            let end = self.lifetime.elapsed().as_micros();
-            self.duration = end - self.duration;
-            self.fps = get_fps();
+        for emitter in self.emitters.iter() {
+            emitter.draw(renderer);
+        }
 
-            self.emitted_particle_count = self
-                .emitters
-                .iter()
-                .fold(0, |acc, emitter| acc + emitter.particle_count);
+        for swarm_emitter in self.swarm_emitters.iter() {
+            swarm_emitter.draw(renderer);
         }
     }
 
     pub fn draw_ui(&mut self) {
+        if self.render_frame.is_multiple_of(50) {
+            self.fps = get_fps();
+        }
+
         draw_text(
             format!(
                 "Particle count: {}",
@@ -357,25 +795,16 @@ impl Grid {
             // x, y on screen is an offset of the the grid position.
             let x = raw_x - self.position.x;
             let y = raw_y - self.position.y;
-            let x_index = self.possibility_x_index(x);
-            let y_index = self.possibility_y_index(y);
-            let vec_index = self.possibility_index(x_index, y_index);
 
             println!("------------------------------");
-            for particle in self.possibility_spots[vec_index].iter() {
-                let inside_x = particle.x <= x && x <= particle.x + particle.diameter;
-                let inside_y = particle.y <= y && y <= particle.y + particle.diameter;
-
-                // No collision
-                if inside_x && inside_y {
-                    println!("{:#?}", particle);
-                }
+            for handle in self.particles_in_radius(x, y, 0.) {
+                println!("{:#?}", self.particle(handle));
             }
         }
 
         fn draw_grid(grid: &Grid) {
-            for x_index in 0..grid.possibility_x_count * grid.cell_x_count {
-                for y_index in 0..grid.possibility_y_count * grid.cell_y_count {
+            for x_index in 0..grid.possibility_columns {
+                for y_index in 0..grid.possibility_rows {
                     let x = grid.position.x + (x_index * grid.possibility_side_length) as f32;
                     let y = grid.position.y + (y_index * grid.possibility_side_length) as f32;
 
@@ -394,28 +823,227 @@ impl Grid {
         //draw_grid(&self);
     }
 
-    fn possibility_taken(&self, x_coord: f32, y_coord: f32) -> bool {
-        let cell_x_index = self.cell_x_index(x_coord);
-        let cell_y_index = self.cell_y_index(y_coord);
+    /// Indices of the spots that can hold particles overlapping the area.
+    fn spots_in_area(
+        &self,
+        min_x: f32,
+        min_y: f32,
+        max_x: f32,
+        max_y: f32,
+    ) -> impl Iterator<Item = usize> {
+        // Spots hold particles by their top left corner.
+        let margin = self.possibility_side_length as f32;
+        let columns = self.possibility_columns;
+        let x_range = self.possibility_x_index(min_x - margin)..=self.possibility_x_index(max_x);
+        let y_range = self.possibility_y_index(min_y - margin)..=self.possibility_y_index(max_y);
+
+        y_range.flat_map(move |y| x_range.clone().map(move |x| columns * y + x))
+    }
 
-        let poss_x_index = self.possibility_x_index(x_coord);
-        let poss_y_index = self.possibility_y_index(y_coord);
+    /// Every particle overlapping the area that passes the filter.
+    fn query<F>(
+        &self,
+        min_x: f32,
+        min_y: f32,
+        max_x: f32,
+        max_y: f32,
+        filter: F,
+    ) -> Vec<ParticleHandle>
+    where
+        F: Fn(&ParticleRef) -> bool,
+    {
+        let mut handles = self.query_grid(min_x, min_y, max_x, max_y, &filter);
+        handles.extend(self.query_emitted(min_x, min_y, max_x, max_y, &filter));
+        handles
+    }
+
+    fn query_grid<F>(
+        &self,
+        min_x: f32,
+        min_y: f32,
+        max_x: f32,
+        max_y: f32,
+        filter: F,
+    ) -> Vec<ParticleHandle>
+    where
+        F: Fn(&ParticleRef) -> bool,
+    {
+        let mut handles = Vec::new();
+
+        for vec_index in self.spots_in_area(min_x, min_y, max_x, max_y) {
+            for particle in self.possibility_spots[vec_index].iter() {
+                if filter(&ParticleRef::Grid(particle)) {
+                    handles.push(ParticleHandle::Grid(particle.id));
+                }
+            }
+        }
+
+        handles
+    }
+
+    /// Emitted particles aren't bucketed, their bounds are checked before the filter runs.
+    fn query_emitted<F>(
+        &self,
+        min_x: f32,
+        min_y: f32,
+        max_x: f32,
+        max_y: f32,
+        filter: F,
+    ) -> Vec<ParticleHandle>
+    where
+        F: Fn(&ParticleRef) -> bool,
+    {
+        let mut handles = Vec::new();
+
+        for emitter in self.emitters.iter() {
+            for particle in emitter.particles().iter() {
+                let outside = particle.x + particle.radius < min_x
+                    || max_x < particle.x - particle.radius
+                    || particle.y + particle.radius < min_y
+                    || max_y < particle.y - particle.radius;
+
+                if !outside && filter(&ParticleRef::Emitted(particle)) {
+                    handles.push(ParticleHandle::Emitted {
+                        emitter: emitter.id,
+                        particle: particle.id,
+                    });
+                }
+            }
+        }
+
+        handles
+    }
+
+    /// Where the particle is stored right now, None if it doesn't live anymore.
+    pub fn locate(&self, handle: ParticleHandle) -> Option<ParticleLocation> {
+        match handle {
+            ParticleHandle::Grid(id) => {
+                let (vec_index, spot_index) = self.particle_slots.get(id)?;
+
+                Some(ParticleLocation::Grid {
+                    vec_index,
+                    spot_index,
+                })
+            }
+            ParticleHandle::Emitted { emitter, particle } => {
+                let emitter_index = self.emitter_slots.get(emitter)?;
+                let particle_index = self.emitters[emitter_index].particle_index(particle)?;
+
+                Some(ParticleLocation::Emitted {
+                    emitter_index,
+                    particle_index,
+                })
+            }
+        }
+    }
+
+    pub fn particle(&self, handle: ParticleHandle) -> Option<ParticleRef<'_>> {
+        match self.locate(handle)? {
+            ParticleLocation::Grid {
+                vec_index,
+                spot_index,
+            } => Some(ParticleRef::Grid(
+                &self.possibility_spots[vec_index][spot_index],
+            )),
+            ParticleLocation::Emitted {
+                emitter_index,
+                particle_index,
+            } => Some(ParticleRef::Emitted(
+                &self.emitters[emitter_index].particles()[particle_index],
+            )),
+        }
+    }
+
+    /// Particles touching the circle around x, y (grid coordinates).
+    pub fn particles_in_radius(&self, x: f32, y: f32, radius: f32) -> Vec<ParticleHandle> {
+        self.query(x - radius, y - radius, x + radius, y + radius, |particle| {
+            let Point(center_x, center_y) = particle.center();
+            let reach = radius + particle.radius();
+
+            (center_x - x).powi(2) + (center_y - y).powi(2) <= reach.powi(2)
+        })
+    }
+
+    /// Particles touching the rectangle with its top left corner at x, y (grid coordinates).
+    pub fn particles_in_rect(
+        &self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    ) -> Vec<ParticleHandle> {
+        self.query(x, y, x + width, y + height, |particle| {
+            let Point(center_x, center_y) = particle.center();
+            let closest_x = center_x.clamp(x, x + width);
+            let closest_y = center_y.clamp(y, y + height);
+
+            (center_x - closest_x).powi(2) + (center_y - closest_y).powi(2)
+                <= particle.radius().powi(2)
+        })
+    }
+
+    /// Particle with its center closest to x, y (grid coordinates).
+    pub fn nearest_particle(&self, x: f32, y: f32) -> Option<ParticleHandle> {
+        let distance = |handle: &ParticleHandle| {
+            let Point(center_x, center_y) = self.particle(*handle).unwrap().center();
+            (center_x - x).powi(2) + (center_y - y).powi(2)
+        };
+
+        let max_radius = self.position.width.hypot(self.position.height);
+        let mut radius = self.possibility_side_length as f32;
+
+        // Grows the search area until it contains a grid particle.
+        loop {
+            let nearest = self
+                .query_grid(x - radius, y - radius, x + radius, y + radius, |_| true)
+                .into_iter()
+                .min_by(|a, b| distance(a).total_cmp(&distance(b)));
+
+            if let Some(handle) = nearest {
+                // Anything closer than the found particle lies inside this radius.
+                let reach = distance(&handle).sqrt();
+                return self
+                    .particles_in_radius(x, y, reach)
+                    .into_iter()
+                    .min_by(|a, b| distance(a).total_cmp(&distance(b)));
+            }
+
+            if max_radius < radius {
+                break;
+            }
+
+            radius *= 2.;
+        }
 
-        self.possibility_spots[self.possibility_index(poss_x_index, poss_y_index)]
+        // No grid particles, only emitted particles are left.
+        self.emitters
             .iter()
-            .any(|p| {
-                self.cell_x_index(p.x) == cell_x_index && self.cell_y_index(p.y) == cell_y_index
+            .flat_map(|emitter| {
+                emitter
+                    .particles()
+                    .iter()
+                    .map(|particle| ParticleHandle::Emitted {
+                        emitter: emitter.id,
+                        particle: particle.id,
+                    })
             })
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+    }
+
+    fn possibility_taken(&self, x_coord: f32, y_coord: f32) -> bool {
+        let poss_x_index = self.possibility_x_index(x_coord);
+        let poss_y_index = self.possibility_y_index(y_coord);
+
+        !self.possibility_spots[self.possibility_index(poss_x_index, poss_y_index)].is_empty()
     }
 
     fn fill_white_noise(&mut self, attributes: &ParticleAttributes, count: u32) {
         let mut i: u32 = 0;
-        let lifetime = Rc::new(Instant::now());
         while i < count {
-            let x_coord = rand::gen_range(0., self.position.width);
-            let y_coord = rand::gen_range(0., self.position.height);
+            let x_coord = self.rng.gen_range(0., self.position.width);
+            let y_coord = self.rng.gen_range(0., self.position.height);
             if !self.possibility_taken(x_coord, y_coord) {
-                self.add_particle(x_coord, y_coord, attributes, lifetime.clone());
+                self.add_particle(x_coord, y_coord, attributes);
                 i += 1;
             }
         }
@@ -426,31 +1054,156 @@ impl Grid {
         x_coord: f32,
         y_coord: f32,
         attributes: &ParticleAttributes,
-        lifetime: Rc<Instant>,
-    ) {
-        let particle = Particle::new(x_coord, y_coord, attributes, lifetime);
+    ) -> ParticleHandle {
+        self.insert_particle(x_coord, y_coord, 0., 0., attributes)
+    }
+
+    /// The velocity is set before the birth sub emitters trigger, so they can inherit it.
+    fn insert_particle(
+        &mut self,
+        x_coord: f32,
+        y_coord: f32,
+        vx: f32,
+        vy: f32,
+        attributes: &ParticleAttributes,
+    ) -> ParticleHandle {
         let poss_x_index = self.possibility_x_index(x_coord);
         let poss_y_index = self.possibility_y_index(y_coord);
         let poss_index = self.possibility_index(poss_x_index, poss_y_index);
+        let spot_index = self.possibility_spots[poss_index].len();
+        let id = self.particle_slots.insert((poss_index, spot_index));
+
+        let mut particle = Particle::new(id, x_coord, y_coord, attributes, self.time, &self.rng);
+        particle.vx = vx;
+        particle.vy = vy;
+        self.trigger(SubEmitterTrigger::Birth, &particle);
         self.possibility_spots[poss_index].push(particle);
+        self.particle_count += 1;
+
+        ParticleHandle::Grid(id)
+    }
+
+    fn remove_particles(&mut self, handles: Vec<ParticleHandle>) -> usize {
+        handles
+            .into_iter()
+            .filter(|handle| self.remove_particle(*handle))
+            .count()
+    }
+}
+
+impl Container for Grid {
+    /// Positions outside of the grid are moved to the closest edge.
+    fn spawn_particle(
+        &mut self,
+        x: f32,
+        y: f32,
+        vx: f32,
+        vy: f32,
+        attributes: &ParticleAttributes,
+    ) -> ParticleHandle {
+        let max_x = self.position.width - WALL_MARGIN - attributes.diameter;
+        let max_y = self.position.height - WALL_MARGIN - attributes.diameter;
+        self.insert_particle(x.clamp(0., max_x), y.clamp(0., max_y), vx, vy, attributes)
+    }
+
+    fn remove_particle(&mut self, handle: ParticleHandle) -> bool {
+        match self.locate(handle) {
+            Some(ParticleLocation::Grid {
+                vec_index,
+                spot_index,
+            }) => {
+                let particle = self.take_particle(vec_index, spot_index);
+                self.kill_particle(particle);
+                true
+            }
+            Some(ParticleLocation::Emitted {
+                emitter_index,
+                particle_index,
+            }) => {
+                let emitter = &mut self.emitters[emitter_index];
+                let id = emitter.particles()[particle_index].id;
+
+                emitter.remove_particle(id);
+                // The count is only refreshed every few frames, so it can lag behind.
+                self.count_emitted_particles();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove_particles_in_radius(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        let handles = self.particles_in_radius(x, y, radius);
+        self.remove_particles(handles)
+    }
+
+    fn remove_particles_in_rect(&mut self, x: f32, y: f32, width: f32, height: f32) -> usize {
+        let handles = self.particles_in_rect(x, y, width, height);
+        self.remove_particles(handles)
+    }
+
+    fn clear(&mut self) {
+        for spot in self.possibility_spots.iter_mut() {
+            spot.clear();
+        }
+
+        self.particle_slots.clear();
+
+        for emitter in self.emitters.iter_mut() {
+            emitter.clear_particles();
+        }
+
+        self.particle_count = 0;
+        self.emitted_particle_count = 0;
+    }
+}
+
+/**
+ * Fraction of the movement at which the particle bounces off 0 or max, infinity if it doesn't.
+ * Only edges with BoundaryMode::Bounce stop the particle.
+ */
+fn wall_impact(start: f32, distance: f32, max: f32, low: BoundaryMode, high: BoundaryMode) -> f32 {
+    let end = start + distance;
+
+    if distance < 0. && end < 0. && low == BoundaryMode::Bounce {
+        start.max(0.) / -distance
+    } else if 0. < distance && max < end && high == BoundaryMode::Bounce {
+        (max - start).max(0.) / distance
+    } else {
+        f32::INFINITY
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::force::force;
-    use crate::grid::test::force::Force;
-    use crate::particle::*;
-    use crate::FillStyle;
-    use crate::ForceBuilder;
-    use crate::ForceType;
+    use crate::boundary::{Boundaries, BoundaryMode};
+    use crate::clock::ManualClock;
+    use crate::container::Container;
+    use crate::emitters::emitter::EmitterOptions;
+    use crate::emitters::emitter_shape::EmitterShape;
+    use crate::emitters::sub_emitter::{SubEmitter, SubEmitterTrigger};
+    use crate::fill_style::FillStyle;
+    use crate::force::constant_force::ConstantForce;
+    use crate::force::force_handler::ForceHandler;
+    use crate::integrators::semi_implicit_euler::SemiImplicitEuler;
+    use crate::movement_handler::{MovementHandler, PathMode};
+    use crate::obstacles::segment_obstacle::SegmentObstacle;
+    use crate::particle::ParticleAttributes;
+    use crate::particle_handle::{ParticleHandle, ParticleLocation, ParticleRef};
+    use crate::point::Point;
+    use crate::substeps::Substeps;
     use crate::Grid;
     use crate::GridOptions;
-    use crate::ParticleAttributes;
     use crate::Position;
     use macroquad::prelude::Color;
+    use std::rc::Rc;
+    use std::time::Duration;
 
     fn default_grid() -> Grid {
+        grid_with_clock(ManualClock::new(), None)
+    }
+
+    fn grid_with_clock(clock: ManualClock, fixed_timestep: Option<Duration>) -> Grid {
         let options = GridOptions {
             cell_x_count: 5,
             cell_y_count: 5,
@@ -458,7 +1211,13 @@ mod test {
             possibility_y_count: 10,
             possibility_side_length: 10,
             position: Position::new(1., 2.),
-            force_handler: Vec::new(),
+            force_handler: None,
+            clock: Box::new(clock),
+            seed: 1,
+            fixed_timestep,
+            integrator: Rc::new(SemiImplicitEuler),
+            substeps: Substeps::Fixed(1),
+            boundaries: Boundaries::all(BoundaryMode::Bounce),
         };
 
         Grid::new(options)
@@ -467,46 +1226,94 @@ mod test {
     fn default_attributes() -> ParticleAttributes {
         ParticleAttributes {
             color: Color::from_rgba(20, 20, 200, 255),
+            texture: None,
             friction_coefficient: 0.5,
             diameter: 5.,
             elasticity: 0.9,
             mass: 1.,
-            animation_start_at: InitAnimation::Zero,
+            animation_options: None,
+            trail_handler: None,
+            continuous_collision: false,
+            max_lifetime: None,
+            fade_out: Duration::ZERO,
+            sub_emitters: Vec::new(),
         }
     }
 
-    fn default_forces() -> Vec<Force> {
-        let mut builder = ForceBuilder::new();
-
-        builder.add(
-            ForceType::Static {
-                vx: 0.02,
-                vy: 0.015,
-            },
-            50,
-        );
-
-        builder.add(ForceType::None, 100);
-
-        builder.add(
-            ForceType::Newton {
-                nx: -0.02,
-                ny: -0.01,
-            },
-            50,
-        );
-
-        builder.add(
-            ForceType::Accelerate {
-                vx: -0.1,
-                vy: -0.01,
-                vx_max: -0.5,
-                vy_max: -1.5,
-            },
-            30,
-        );
+    fn default_emitter_options() -> EmitterOptions {
+        EmitterOptions {
+            emitter_position: Position::new(250., 250.),
+            emitter_diameter: 0.,
+            emitter_shape: EmitterShape::Line,
+            emit_along_normal: false,
+            emitter_duration: Duration::from_secs(10),
+            angle_degrees: 0.,
+            diffusion_degrees: 0.,
+            emission_distortion_px: 0.,
+            particle_color: Color::from_rgba(200, 20, 20, 255),
+            particle_texture: None,
+            particles_per_emission: 1,
+            delay_between_emission: Duration::from_secs(1),
+            particle_lifetime: Duration::from_secs(10),
+            particle_radius: 2.5,
+            particle_mass: 1.,
+            particle_elasticity: 1.,
+            particle_speed: 0.,
+            particle_friction_coefficient: 0.,
+            boundaries: Boundaries::all(BoundaryMode::Open),
+            particle_animation_options: None,
+            emitter_animation_handler: None,
+            force_handler: None,
+            trail_handler: None,
+            movement_handler: None,
+            align_emission_to_movement: false,
+            seed: None,
+            integrator: None,
+            collide_with_grid: true,
+            sub_emitters: Vec::new(),
+        }
+    }
 
-        builder.build()
+    fn default_forces() -> ForceHandler {
+        let mut force_handler = ForceHandler::new(Duration::from_millis(230));
+
+        force_handler.add(Box::new(ConstantForce {
+            nx: 0.02,
+            ny: 0.015,
+            max_vx: 1.,
+            max_vy: 1.,
+            from_ms: 0,
+            until_ms: 50,
+        }));
+
+        force_handler.add(Box::new(ConstantForce {
+            nx: -0.02,
+            ny: -0.01,
+            max_vx: -1.,
+            max_vy: -1.,
+            from_ms: 150,
+            until_ms: 200,
+        }));
+
+        force_handler.add(Box::new(ConstantForce {
+            nx: -0.1,
+            ny: -0.01,
+            max_vx: -0.5,
+            max_vy: -1.5,
+            from_ms: 200,
+            until_ms: 230,
+        }));
+
+        force_handler.add(Box::new(ConstantForce {
+            nx: 0.,
+            ny: 0.,
+            max_vx: 0.,
+            max_vy: 0.,
+            from_ms: 50,
+            until_ms: 150,
+        }));
+
+        force_handler
     }
 
     #[test]
@@ -514,9 +1321,9 @@ mod test {
         let grid = default_grid();
         assert_eq!(grid.cell_width, 100); // 10 * 10
         assert_eq!(grid.cell_height, 100);
-        assert_eq!(grid.possibility_spots.len(), 100); // 10 * 10
-        assert_eq!(grid.width, 500.);
-        assert_eq!(grid.height, 500.);
+        assert_eq!(grid.possibility_spots.len(), 2500); // (5 * 10) * (5 * 10)
+        assert_eq!(grid.position.width, 500.);
+        assert_eq!(grid.position.height, 500.);
         assert_eq!(grid.position.x, 1.);
         assert_eq!(grid.position.y, 2.);
     }
@@ -538,73 +1345,628 @@ mod test {
         assert_eq!(grid.particle_count, len);
     }
 
+    #[test]
+    fn fill_grid_with_seed() {
+        let attributes = default_attributes();
+        let mut grid_1 = default_grid();
+        let mut grid_2 = default_grid();
+
+        grid_1.fill(&attributes, 50, FillStyle::WhiteNoise);
+        grid_2.fill(&attributes, 50, FillStyle::WhiteNoise);
+
+        let particles_1 = grid_1.possibility_spots.iter().flatten();
+        let particles_2 = grid_2.possibility_spots.iter().flatten();
+
+        for (particle_1, particle_2) in particles_1.zip(particles_2) {
+            assert_eq!(particle_1.x, particle_2.x);
+            assert_eq!(particle_1.y, particle_2.y);
+        }
+    }
+
     #[test]
     fn updates_frame() {
         let mut grid = default_grid();
         assert_eq!(0, grid.frame);
-        grid.draw();
+        grid.step();
         assert_eq!(1, grid.frame);
     }
 
     #[test]
-    fn add_particle() {
+    fn add_particle_to_spot() {
         let mut grid = default_grid();
         let attributes = default_attributes();
 
         grid.add_particle(115., 105., &attributes);
-        assert_eq!(1, grid.possibility_spots[1].len());
+        assert_eq!(1, grid.possibility_spots[511].len());
 
-        // if y is 1 more, then the pos in array is + poss_x_count (10).
-        grid.add_particle(105., 115., &attributes);
-        assert_eq!(1, grid.possibility_spots[10].len());
+        // if y is 1 more, then the pos in array is + possibility_columns (50).
+        grid.add_particle(115., 115., &attributes);
+        assert_eq!(1, grid.possibility_spots[561].len());
 
-        let particle = &grid.possibility_spots[1][0];
+        // Same position inside another cell doesn't share the spot.
+        grid.add_particle(15., 5., &attributes);
+        assert_eq!(1, grid.possibility_spots[1].len());
+        assert_eq!(1, grid.possibility_spots[511].len());
 
-        assert_eq!(1, grid.possibility_x_index(particle.x));
-        assert_eq!(0, grid.possibility_y_index(particle.y));
+        let particle = &grid.possibility_spots[511][0];
 
-        assert_eq!(1, grid.cell_x_index(particle.x));
-        assert_eq!(1, grid.cell_y_index(particle.y));
+        assert_eq!(11, grid.possibility_x_index(particle.x));
+        assert_eq!(10, grid.possibility_y_index(particle.y));
 
         // colors is a number between 0 - 1, (255 / 255).
         assert_eq!(1., particle.color.a);
 
         assert_eq!(5., particle.diameter);
         assert_eq!(2.5, particle.radius);
-        assert_eq!(0.5, particle.friction);
+        assert_eq!(0.5, particle.friction_coefficient);
         assert_eq!(0.9, particle.elasticity);
         assert_eq!(1., particle.mass);
     }
 
-    #[macroquad::test]
-    async fn moves_particle() {
-        let mut grid = default_grid();
+    #[test]
+    fn moves_particle() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
         let attributes = default_attributes();
 
         grid.add_particle(5., 5., &attributes);
 
         assert_eq!(1, grid.possibility_spots[0].len());
 
-        grid.possibility_spots[0][0].vx += 10.;
-        grid.draw();
+        grid.possibility_spots[0][0].vx += 100.;
+        clock.advance(Duration::from_millis(100));
+        grid.step();
 
         assert_eq!(1, grid.possibility_spots[1].len());
         assert_eq!(0, grid.possibility_spots[0].len());
     }
 
+    #[test]
+    fn collides_across_cells() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        let attributes = default_attributes();
+
+        // Spot 9 in the first cell and spot 10 in the second cell, overlapping each other.
+        grid.add_particle(96., 100., &attributes);
+        grid.add_particle(100.5, 100., &attributes);
+        grid.possibility_spots[509][0].vx = 50.;
+
+        clock.advance(Duration::from_millis(10));
+        grid.step();
+
+        let left = &grid.possibility_spots[509][0];
+        let right = &grid.possibility_spots[510][0];
+
+        assert!(left.vx < right.vx);
+        assert!(left.diameter <= right.x - left.x);
+    }
+
+    #[test]
+    fn sweeps_fast_particles() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        let mut attributes = default_attributes();
+        attributes.continuous_collision = true;
+
+        grid.add_particle(100., 100., &attributes);
+        grid.add_particle(150., 100., &default_attributes());
+        // Moves 100 px in a single step, way past the other particle.
+        grid.possibility_spots[510][0].vx = 10000.;
+
+        clock.advance(Duration::from_millis(10));
+        grid.step();
+
+        let particles = || grid.possibility_spots.iter().flatten();
+        let fast = particles().find(|p| p.continuous_collision).unwrap();
+        let hit = particles().find(|p| !p.continuous_collision).unwrap();
+
+        assert!(fast.x < hit.x);
+        assert!(fast.vx < hit.vx);
+    }
+
+    #[test]
+    fn collides_emitted_particles() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        grid.add_emitter(default_emitter_options());
+        grid.add_emitter(default_emitter_options());
+        grid.step();
+
+        // Both emitters emit at the center of the same spot.
+        let particles = grid.emitters.iter().flat_map(|emitter| emitter.particles());
+        let distance = particles
+            .map(|particle| particle.x)
+            .reduce(|a, b| (a - b).abs());
+        assert!(4.99 <= distance.unwrap());
+
+        grid.add_particle(255., 248., &default_attributes());
+        grid.emitters[1].particles_mut()[0].vx = 300.;
+        clock.advance(Duration::from_millis(10));
+        grid.step();
+
+        let particle = grid.possibility_spots.iter().flatten().next().unwrap();
+        assert!(0. < particle.vx);
+    }
+
+    #[test]
+    fn bounces_off_obstacles() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        grid.add_obstacle(Box::new(SegmentObstacle {
+            start: Point(80., 106.),
+            end: Point(130., 106.),
+            thickness: 2.,
+            color: Color::from_rgba(255, 255, 255, 255),
+        }));
+
+        grid.add_particle(100., 100., &default_attributes());
+        grid.possibility_spots[510][0].vy = 100.;
+        clock.advance(Duration::from_millis(10));
+        grid.step();
+
+        let particle = grid.possibility_spots.iter().flatten().next().unwrap();
+        assert!(particle.vy < 0.);
+        assert!(particle.y + particle.diameter <= 105.);
+    }
+
+    #[test]
+    fn applies_boundaries() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        grid.boundaries.bottom = BoundaryMode::Kill;
+        grid.boundaries.right = BoundaryMode::Wrap;
+
+        grid.add_particle(100., 490., &default_attributes());
+        grid.add_particle(490., 100., &default_attributes());
+        grid.possibility_spots[2460][0].vy = 1000.;
+        grid.possibility_spots[549][0].vx = 1000.;
+        clock.advance(Duration::from_millis(10));
+        grid.step();
+
+        assert_eq!(1, grid.particle_count);
+        let particle = grid.possibility_spots.iter().flatten().next().unwrap();
+        assert!(particle.x < 10.);
+        assert!(0. < particle.vx);
+    }
+
+    #[test]
+    fn removes_expired_particles() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        let mut attributes = default_attributes();
+        attributes.max_lifetime = Some(Duration::from_millis(100));
+        attributes.fade_out = Duration::from_millis(50);
+
+        grid.add_particle(100., 100., &attributes);
+        grid.add_particle(200., 200., &default_attributes());
+
+        clock.advance(Duration::from_millis(75));
+        grid.step();
+        assert_eq!(2, grid.particle_count);
+
+        let fading = grid.possibility_spots[510].first().unwrap();
+        assert!((fading.opacity - 0.5).abs() < 0.01);
+
+        clock.advance(Duration::from_millis(25));
+        grid.step();
+        assert_eq!(1, grid.particle_count);
+        assert_eq!(1, grid.possibility_spots.iter().flatten().count());
+    }
+
+    fn burst() -> EmitterOptions {
+        let mut options = default_emitter_options();
+        options.particle_color = Color::from_rgba(0, 0, 0, 255);
+        options.particles_per_emission = 2;
+        options.collide_with_grid = false;
+        options
+    }
+
+    #[test]
+    fn triggers_sub_emitters() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+
+        let mut rocket = default_emitter_options();
+        rocket.particle_speed = 100.;
+        rocket.particle_lifetime = Duration::from_millis(50);
+        rocket.emitter_duration = Duration::ZERO;
+        rocket.collide_with_grid = false;
+        rocket.sub_emitters = vec![SubEmitter {
+            trigger: SubEmitterTrigger::Death,
+            options: burst,
+            inherit_color: true,
+            inherit_velocity: 0.5,
+        }];
+        grid.add_emitter(rocket);
+
+        let mut attributes = default_attributes();
+        attributes.sub_emitters = vec![SubEmitter {
+            trigger: SubEmitterTrigger::Collision,
+            options: burst,
+            inherit_color: false,
+            inherit_velocity: 0.,
+        }];
+        grid.spawn_particle(100., 100., 0., 0., &attributes);
+        grid.spawn_particle(103., 100., 0., 0., &attributes);
+
+        grid.step();
+
+        // Both overlapping grid particles burst.
+        assert_eq!(3, grid.emitters.len());
+
+        for _ in 0..2 {
+            clock.advance(Duration::from_millis(20));
+            grid.step();
+        }
+
+        assert_eq!(3, grid.emitters.len());
+
+        // The rocket burns out a few px above the emitter, the burst shows up in the same frame.
+        clock.advance(Duration::from_millis(20));
+        grid.step();
+
+        let burst = grid.emitters.last().unwrap();
+        let particle = &burst.particles()[0];
+        assert_eq!(3, grid.emitters.len());
+        assert_eq!(2, burst.particles().len());
+        assert_eq!(Duration::from_millis(60), particle.spawned_at);
+        assert_eq!(Color::from_rgba(200, 20, 20, 255), particle.color);
+        assert!((particle.vy + 50.).abs() < 1.);
+        assert!(240. < particle.y && particle.y < 245.);
+    }
+
+    #[test]
+    fn passes_spawn_and_death_state_to_sub_emitters() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        grid.boundaries = Boundaries::all(BoundaryMode::Kill);
+
+        let mut attributes = default_attributes();
+        attributes.friction_coefficient = 0.;
+        attributes.sub_emitters = vec![
+            SubEmitter {
+                trigger: SubEmitterTrigger::Birth,
+                options: burst,
+                inherit_color: false,
+                inherit_velocity: 1.,
+            },
+            SubEmitter {
+                trigger: SubEmitterTrigger::Death,
+                options: burst,
+                inherit_color: false,
+                inherit_velocity: 0.,
+            },
+        ];
+
+        grid.spawn_particle(480., 100., 1000., 0., &attributes);
+        grid.step();
+
+        // Birth inherits the velocity the particle was spawned with.
+        let birth = &grid.emitters[0].particles()[0];
+        assert!((birth.vx - 1000.).abs() < 1.);
+
+        clock.advance(Duration::from_millis(20));
+        grid.step();
+
+        // Killed past the right edge, the burst starts where the particle got to.
+        assert_eq!(0, grid.particle_count);
+        let death = &grid.emitters.last().unwrap().particles()[0];
+        assert!(500. < death.x);
+    }
+
+    #[test]
+    fn caps_missed_emissions() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+
+        let mut options = default_emitter_options();
+        options.delay_between_emission = Duration::from_millis(10);
+        options.collide_with_grid = false;
+        grid.add_emitter(options);
+
+        let mut options = default_emitter_options();
+        options.delay_between_emission = Duration::ZERO;
+        options.collide_with_grid = false;
+        grid.add_emitter(options);
+
+        grid.step();
+        assert_eq!(1, grid.emitters[0].particles().len());
+
+        // A stall of a second only makes up for the last few emissions.
+        clock.advance(Duration::from_secs(1));
+        grid.step();
+        assert_eq!(9, grid.emitters[0].particles().len());
+        assert!(grid.emitters[1].particles().len() <= 10);
+    }
+
+    #[test]
+    fn tracks_particles_by_handle() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        let mut attributes = default_attributes();
+        attributes.friction_coefficient = 0.;
+
+        // Shares a spot with the tracked particle, and takes its place when it moves.
+        let neighbour = grid.spawn_particle(100., 100., 0., 0., &attributes);
+        let tracked = grid.spawn_particle(106., 100., 500., 0., &attributes);
+        grid.spawn_particle(104., 104., 0., 0., &attributes);
+
+        let mut options = default_emitter_options();
+        options.emitter_duration = Duration::ZERO;
+        grid.add_emitter(options);
+        grid.add_emitter(default_emitter_options());
+
+        clock.advance(Duration::from_millis(100));
+        grid.step();
+
+        let particle = grid.particle(tracked).unwrap();
+        assert!(150. < particle.center().0);
+        assert!(grid.particle(neighbour).unwrap().center().0 < 110.);
+
+        // The first emitter finished, the remaining one moved to its index.
+        assert_eq!(1, grid.emitters.len());
+        let emitted = grid.particles_in_radius(250., 250., 1.)[0];
+        assert!(matches!(
+            grid.particle(emitted),
+            Some(ParticleRef::Emitted(_))
+        ));
+
+        assert!(grid.remove_particle(tracked));
+        assert!(grid.particle(tracked).is_none());
+        assert!(grid.particle(neighbour).is_some());
+
+        // Takes the slot of the removed particle without reviving its handle.
+        grid.spawn_particle(300., 300., 0., 0., &attributes);
+        assert!(grid.particle(tracked).is_none());
+    }
+
+    #[test]
+    fn spawns_and_removes_particles() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        let attributes = default_attributes();
+
+        let handle = grid.spawn_particle(100., 100., 100., 0., &attributes);
+        grid.spawn_particle(101., 101., 0., 0., &attributes);
+        grid.spawn_particle(300., 300., 0., 0., &attributes);
+        grid.spawn_particle(900., -20., 0., 0., &attributes);
+        assert_eq!(4, grid.particle_count);
+
+        let spawned = grid.particle(handle).unwrap();
+        assert_eq!(
+            (100., 100.),
+            (spawned.center().0 - 2.5, spawned.velocity().0)
+        );
+        assert_eq!(2, grid.remove_particles_in_rect(95., 95., 10., 10.));
+        assert_eq!(2, grid.particle_count);
+
+        let corner = grid.particles_in_radius(497.5, 2.5, 1.);
+        assert_eq!(1, corner.len());
+        assert!(grid.remove_particle(corner[0]));
+        assert!(!grid.remove_particle(corner[0]));
+
+        grid.add_emitter(default_emitter_options());
+        clock.advance(Duration::from_millis(10));
+        grid.step();
+        assert_eq!(1, grid.emitted_particle_count);
+
+        grid.clear();
+        assert_eq!(0, grid.particle_count + grid.emitted_particle_count);
+        assert_eq!(0, grid.possibility_spots.iter().flatten().count());
+        assert!(grid.emitters[0].particles().is_empty());
+    }
+
+    #[test]
+    fn removes_emitted_particles_between_counts() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+
+        // Counted on the first frame, before anything got emitted.
+        grid.step();
+        grid.add_emitter(default_emitter_options());
+        clock.advance(Duration::from_millis(10));
+        grid.step();
+        assert_eq!(0, grid.emitted_particle_count);
+
+        let emitted = grid.particles_in_radius(250., 250., 5.);
+        assert_eq!(1, emitted.len());
+        assert!(grid.remove_particle(emitted[0]));
+        assert_eq!(0, grid.emitted_particle_count);
+        assert_eq!(0, grid.possibility_spots.iter().flatten().count());
+        assert!(grid.emitters[0].particles().is_empty());
+    }
+
+    #[test]
+    fn aligns_emission_to_movement() {
+        let mut options = default_emitter_options();
+        let flight_path = vec![Point(400., 250.)];
+        options.movement_handler = Some(MovementHandler::new(flight_path, 100., PathMode::Once));
+        options.align_emission_to_movement = true;
+        options.particle_speed = 50.;
+
+        let mut grid = default_grid();
+        grid.add_emitter(options);
+        grid.step();
+
+        let particle = &grid.emitters[0].particles()[0];
+        assert!((particle.vx - 50.).abs() < 0.01);
+        assert!(particle.vy.abs() < 0.01);
+    }
+
+    #[test]
+    fn moves_independent_of_frame_rate() {
+        let mut attributes = default_attributes();
+        attributes.friction_coefficient = 0.;
+
+        let clock_1 = ManualClock::new();
+        let mut grid_1 = grid_with_clock(clock_1.clone(), None);
+        grid_1.add_particle(100., 100., &attributes);
+        grid_1.possibility_spots[510][0].vx = 100.;
+
+        let clock_2 = ManualClock::new();
+        let mut grid_2 = grid_with_clock(clock_2.clone(), None);
+        grid_2.add_particle(100., 100., &attributes);
+        grid_2.possibility_spots[510][0].vx = 100.;
+
+        clock_1.advance(Duration::from_secs(1));
+        grid_1.step();
+
+        for _ in 0..10 {
+            clock_2.advance(Duration::from_millis(100));
+            grid_2.step();
+        }
+
+        let particle_1 = grid_1.possibility_spots.iter().flatten().next().unwrap();
+        let particle_2 = grid_2.possibility_spots.iter().flatten().next().unwrap();
+
+        assert!((particle_1.x - 200.).abs() < 0.01);
+        assert!((particle_1.x - particle_2.x).abs() < 0.01);
+    }
+
+    #[test]
+    fn steps_with_fixed_timestep() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), Some(Duration::from_millis(10)));
+
+        clock.advance(Duration::from_millis(35));
+        grid.step();
+        assert_eq!(3, grid.frame);
+        assert_eq!(Duration::from_millis(30), grid.time);
+
+        clock.advance(Duration::from_millis(5));
+        grid.step();
+        assert_eq!(4, grid.frame);
+
+        // Drops the backlog instead of spiraling when physics can't keep up.
+        clock.advance(Duration::from_secs(10));
+        grid.step();
+        assert_eq!(12, grid.frame);
+
+        // Every step is one rendered frame, no matter how many updates it ran.
+        assert_eq!(3, grid.render_frame);
+    }
+
+    #[test]
+    fn adapts_substeps_to_velocity() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        grid.substeps = Substeps::Adaptive {
+            max_distance: 10.,
+            max_substeps: 20,
+        };
+
+        grid.add_particle(100., 100., &default_attributes());
+        grid.possibility_spots[510][0].vx = 1000.;
+
+        clock.advance(Duration::from_millis(100));
+        grid.step();
+
+        assert_eq!(1, grid.frame);
+        assert_eq!(10, grid.substep);
+        assert_eq!(Duration::from_millis(100), grid.time);
+    }
+
     #[test]
     fn add_external_forces() {
-        let forces = default_forces();
+        let force_handler = default_forces();
+
+        assert_eq!(force_handler.forces.len(), 4);
+        assert_eq!(force_handler.duration_ms, 230);
+    }
+
+    #[test]
+    fn rebuckets_pushed_particles() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        let mut attributes = default_attributes();
+        attributes.friction_coefficient = 0.;
+
+        // Updated before the particle that runs into it from the spot to the right.
+        let pushed = grid.spawn_particle(10.5, 5., 0., 0., &attributes);
+        grid.spawn_particle(21., 5., -500., 0., &attributes);
+
+        clock.advance(Duration::from_millis(16));
+        grid.step();
+
+        let particle = grid.particle(pushed).unwrap();
+        assert!(particle.center().0 < 12.5);
+        assert!(matches!(
+            grid.locate(pushed),
+            Some(ParticleLocation::Grid { vec_index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn queries_particles_in_radius() {
+        let mut grid = default_grid();
+        let attributes = default_attributes();
+        grid.add_particle(100., 100., &attributes);
+        grid.add_particle(108., 100., &attributes);
+        grid.add_particle(300., 300., &attributes);
+
+        // Centers at 102.5 and 110.5, the query circle touches both edges.
+        let handles = grid.particles_in_radius(106.5, 102.5, 1.5);
+        assert_eq!(handles.len(), 2);
+
+        let handles = grid.particles_in_radius(102.5, 102.5, 0.);
+        assert_eq!(handles.len(), 1);
+        let center = grid.particle(handles[0]).unwrap().center();
+        assert_eq!(center.0, 102.5);
+    }
 
-        assert_eq!(forces.len(), 4);
-        assert_eq!(forces.last().unwrap().until_frame, 230);
+    #[test]
+    fn queries_particles_in_rect() {
+        let mut grid = default_grid();
+        let attributes = default_attributes();
+        grid.add_particle(100., 100., &attributes);
+        grid.add_particle(200., 100., &attributes);
+        grid.add_particle(300., 300., &attributes);
+
+        assert_eq!(grid.particles_in_rect(90., 90., 120., 20.).len(), 2);
+        assert_eq!(grid.particles_in_rect(0., 0., 99., 99.).len(), 0);
+    }
+
+    #[test]
+    fn finds_nearest_particle() {
+        let mut grid = default_grid();
+        let attributes = default_attributes();
+        assert_eq!(grid.nearest_particle(0., 0.), None);
+
+        grid.add_particle(100., 100., &attributes);
+        grid.add_particle(400., 400., &attributes);
+
+        let handle = grid.nearest_particle(350., 300.).unwrap();
+        let center = grid.particle(handle).unwrap().center();
+        assert_eq!(center.0, 402.5);
+    }
+
+    #[test]
+    fn queries_emitted_particles() {
+        let clock = ManualClock::new();
+        let mut grid = grid_with_clock(clock.clone(), None);
+        grid.add_emitter(default_emitter_options());
+
+        clock.advance(Duration::from_millis(16));
+        grid.step();
+
+        let Point(x, y) = match grid.particles_in_radius(250., 250., 5.)[..] {
+            [handle] => grid.particle(handle).unwrap().center(),
+            _ => panic!("expected one emitted particle"),
+        };
+
+        assert!(grid.particles_in_rect(x + 5., y, 10., 10.).is_empty());
+        assert_eq!(1, grid.particles_in_rect(x, y, 10., 10.).len());
+
+        // Without grid particles the emitted particle is the nearest one.
+        let handle = grid.nearest_particle(0., 0.).unwrap();
+        assert!(matches!(handle, ParticleHandle::Emitted { .. }));
     }
 
     #[test]
     fn apply_forces() {
-        // TODO
         let attributes = default_attributes();
-        let forces = default_forces();
+        let clock = ManualClock::new();
 
         let options = GridOptions {
             cell_x_count: 5,
@@ -613,9 +1975,30 @@ mod test {
             possibility_y_count: 10,
             possibility_side_length: 10,
             position: Position::new(1., 2.),
-            force_handler: forces,
+            force_handler: Some(default_forces()),
+            clock: Box::new(clock.clone()),
+            seed: 1,
+            fixed_timestep: None,
+            integrator: Rc::new(SemiImplicitEuler),
+            substeps: Substeps::Fixed(1),
+            boundaries: Boundaries::all(BoundaryMode::Bounce),
         };
 
         let mut grid = Grid::new(options);
+        grid.add_particle(250., 250., &attributes);
+        clock.set(Duration::from_millis(10));
+        grid.step();
+
+        let particle = grid.possibility_spots.iter().flatten().next().unwrap();
+        assert!(0. < particle.vx);
+        assert!(0. < particle.vy);
+
+        // Nothing is applied between 50 and 150 ms, so only friction slows the particle down.
+        let vx = particle.vx;
+        clock.set(Duration::from_millis(100));
+        grid.step();
+
+        let particle = grid.possibility_spots.iter().flatten().next().unwrap();
+        assert!(particle.vx < vx);
     }
 }
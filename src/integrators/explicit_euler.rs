@@ -0,0 +1,15 @@
+use super::integrator::{Acceleration, IntegrationData, Integrator};
+
+/// Moves with the velocity from the start of the step. Cheapest, but gains energy over time.
+pub struct ExplicitEuler;
+
+impl Integrator for ExplicitEuler {
+    fn integrate(&self, data: &mut IntegrationData, dt: f32, acceleration: &mut Acceleration) {
+        let (ax, ay) = acceleration(data);
+
+        data.x += data.vx * dt;
+        data.y += data.vy * dt;
+        data.vx += ax * dt;
+        data.vy += ay * dt;
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::Debug;
+
+/// Moves a particle forward in time, the acceleration callback returns (ax, ay) in px/s² for a
+/// given state, so an integrator can sample the forces as often as it needs.
+pub trait Integrator {
+    fn integrate(&self, data: &mut IntegrationData, dt: f32, acceleration: &mut Acceleration);
+}
+
+pub type Acceleration<'a> = dyn FnMut(&IntegrationData) -> (f32, f32) + 'a;
+
+impl Debug for dyn Integrator {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Integrator")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IntegrationData {
+    pub x: f32,
+    pub y: f32,
+    /// px/s
+    pub vx: f32,
+    /// px/s
+    pub vy: f32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IntegrationData, Integrator};
+    use crate::force::force::{Force, ForceData};
+    use crate::force::gravitational_force::GravitationalForce;
+    use crate::integrators::explicit_euler::ExplicitEuler;
+    use crate::integrators::runge_kutta::RungeKutta4;
+    use crate::integrators::semi_implicit_euler::SemiImplicitEuler;
+    use crate::integrators::velocity_verlet::VelocityVerlet;
+    use crate::path::Path;
+    use crate::point::Point;
+
+    /// Circular orbit around the origin with radius 100, returns the radius after 10 orbits.
+    fn orbit_radius(integrator: &dyn Integrator) -> f32 {
+        let gm = 100_f32.powi(3);
+        let speed = (gm / 100.).sqrt();
+        let mut data = IntegrationData {
+            x: 100.,
+            y: 0.,
+            vx: 0.,
+            vy: speed,
+        };
+
+        let period = 2. * std::f32::consts::PI * 100. / speed;
+        let dt = period / 100.;
+
+        let gravity = GravitationalForce {
+            gravitation_force: gm,
+            dead_zone: 0.,
+            mass: 1.,
+            from_ms: 0,
+            until_ms: u128::MAX,
+            path: Path::line(Point(0., 0.), Point(0., 0.)),
+        };
+
+        for _ in 0..1000 {
+            integrator.integrate(&mut data, dt, &mut |state| {
+                let mut force_data = ForceData {
+                    x: state.x,
+                    y: state.y,
+                    vx: state.vx,
+                    vy: state.vy,
+                    radius: 0.,
+                    mass: 1.,
+                    ax: 0.,
+                    ay: 0.,
+                };
+
+                gravity.apply(&mut force_data, 0);
+                (force_data.ax, force_data.ay)
+            });
+        }
+
+        (data.x.powi(2) + data.y.powi(2)).sqrt()
+    }
+
+    #[test]
+    fn explicit_euler_drifts() {
+        assert!(150. < orbit_radius(&ExplicitEuler));
+    }
+
+    #[test]
+    fn stable_orbits() {
+        assert!((orbit_radius(&SemiImplicitEuler) - 100.).abs() < 5.);
+        assert!((orbit_radius(&VelocityVerlet) - 100.).abs() < 1.);
+        assert!((orbit_radius(&RungeKutta4) - 100.).abs() < 1.);
+    }
+}
@@ -0,0 +1,7 @@
+#![allow(clippy::module_inception)]
+
+pub mod explicit_euler;
+pub mod integrator;
+pub mod runge_kutta;
+pub mod semi_implicit_euler;
+pub mod velocity_verlet;
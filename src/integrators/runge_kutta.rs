@@ -0,0 +1,57 @@
+use super::integrator::{Acceleration, IntegrationData, Integrator};
+
+/// Classic fourth order Runge-Kutta, most accurate, samples the acceleration four times per step.
+pub struct RungeKutta4;
+
+struct Derivative {
+    dx: f32,
+    dy: f32,
+    dvx: f32,
+    dvy: f32,
+}
+
+fn evaluate(
+    data: &IntegrationData,
+    derivative: &Derivative,
+    dt: f32,
+    acceleration: &mut Acceleration,
+) -> Derivative {
+    let state = IntegrationData {
+        x: data.x + derivative.dx * dt,
+        y: data.y + derivative.dy * dt,
+        vx: data.vx + derivative.dvx * dt,
+        vy: data.vy + derivative.dvy * dt,
+    };
+
+    let (ax, ay) = acceleration(&state);
+
+    Derivative {
+        dx: state.vx,
+        dy: state.vy,
+        dvx: ax,
+        dvy: ay,
+    }
+}
+
+impl Integrator for RungeKutta4 {
+    fn integrate(&self, data: &mut IntegrationData, dt: f32, acceleration: &mut Acceleration) {
+        let start = Derivative {
+            dx: 0.,
+            dy: 0.,
+            dvx: 0.,
+            dvy: 0.,
+        };
+
+        let k1 = evaluate(data, &start, 0., acceleration);
+        let k2 = evaluate(data, &k1, dt * 0.5, acceleration);
+        let k3 = evaluate(data, &k2, dt * 0.5, acceleration);
+        let k4 = evaluate(data, &k3, dt, acceleration);
+
+        let sum = |a: f32, b: f32, c: f32, d: f32| (a + 2. * (b + c) + d) / 6.;
+
+        data.x += sum(k1.dx, k2.dx, k3.dx, k4.dx) * dt;
+        data.y += sum(k1.dy, k2.dy, k3.dy, k4.dy) * dt;
+        data.vx += sum(k1.dvx, k2.dvx, k3.dvx, k4.dvx) * dt;
+        data.vy += sum(k1.dvy, k2.dvy, k3.dvy, k4.dvy) * dt;
+    }
+}
@@ -0,0 +1,16 @@
+use super::integrator::{Acceleration, IntegrationData, Integrator};
+
+/// Updates the velocity first and moves with the new velocity, keeps orbits stable for the price
+/// of explicit euler.
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn integrate(&self, data: &mut IntegrationData, dt: f32, acceleration: &mut Acceleration) {
+        let (ax, ay) = acceleration(data);
+
+        data.vx += ax * dt;
+        data.vy += ay * dt;
+        data.x += data.vx * dt;
+        data.y += data.vy * dt;
+    }
+}
@@ -0,0 +1,25 @@
+use super::integrator::{Acceleration, IntegrationData, Integrator};
+
+/// Second order accurate and time reversible, samples the acceleration twice per step.
+pub struct VelocityVerlet;
+
+impl Integrator for VelocityVerlet {
+    fn integrate(&self, data: &mut IntegrationData, dt: f32, acceleration: &mut Acceleration) {
+        let (ax, ay) = acceleration(data);
+
+        data.x += data.vx * dt + 0.5 * ax * dt * dt;
+        data.y += data.vy * dt + 0.5 * ay * dt * dt;
+
+        // Velocity dependent forces get the predicted velocity.
+        let predicted = IntegrationData {
+            vx: data.vx + ax * dt,
+            vy: data.vy + ay * dt,
+            ..*data
+        };
+
+        let (new_ax, new_ay) = acceleration(&predicted);
+
+        data.vx += 0.5 * (ax + new_ax) * dt;
+        data.vy += 0.5 * (ay + new_ay) * dt;
+    }
+}
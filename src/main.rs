@@ -1,27 +1,43 @@
 #![allow(dead_code)]
 
 mod animations;
+mod boundary;
+mod clock;
 mod collision;
 mod container;
+mod easing;
 mod emitters;
 mod fill_style;
 mod force;
+mod gradient;
 mod grid;
+mod integrators;
 mod movement_handler;
+mod obstacles;
 mod particle;
+mod particle_handle;
+mod path;
 mod pattern;
 mod point;
 mod position;
+mod renderer;
+mod rng;
+mod slot_map;
+mod substeps;
 mod swarm_emitter;
 mod trails;
 
+use boundary::{Boundaries, BoundaryMode};
+use clock::RealTimeClock;
 use grid::{Grid, GridOptions};
+use integrators::velocity_verlet::VelocityVerlet;
 
-use fill_style::FillStyle;
 use macroquad::prelude::*;
-use particle::ParticleAttributes;
-use pattern::{another_emitter, random_forces, shimmer_animations, smoke, trail_animation};
+use pattern::{another_emitter, random_forces};
 use position::Position;
+use renderer::MacroquadRenderer;
+use std::rc::Rc;
+use substeps::Substeps;
 
 #[macroquad::main("Particles")]
 async fn main() {
@@ -35,11 +51,20 @@ async fn main() {
         possibility_side_length: 10,
         position,
         force_handler: random_forces(),
+        clock: Box::new(RealTimeClock::new()),
+        seed: 1,
+        fixed_timestep: None,
+        integrator: Rc::new(VelocityVerlet),
+        substeps: Substeps::Adaptive {
+            max_distance: 2.,
+            max_substeps: 8,
+        },
+        boundaries: Boundaries::all(BoundaryMode::Bounce),
     });
 
     //let attributes = ParticleAttributes {
     //color: Color::from_rgba(0, 255, 255, 255),
-    //friction_coefficient: 0.005,
+    //friction_coefficient: 0.26,
     //diameter: 5.5,
     //elasticity: 1.,
     //mass: 3.8,
@@ -52,7 +77,7 @@ async fn main() {
     //let attributes = ParticleAttributes {
     //color: Color::from_rgba(255, 255, 255, 255),
     //texture: Some(texture),
-    //friction_coefficient: 0.001,
+    //friction_coefficient: 0.06,
     //diameter: 6.,
     //elasticity: 1.,
     //mass: 2.0,
@@ -66,7 +91,7 @@ async fn main() {
     ////color: Color::from_rgba(231, 196, 150, 255),
     //color: Color::from_rgba(0, 255, 0, 255),
     //texture: None,
-    //friction_coefficient: 0.008,
+    //friction_coefficient: 0.38,
     //diameter: 7.,
     //elasticity: 1.,
     //mass: 3.,
@@ -78,13 +103,18 @@ async fn main() {
     //grid.fill(&attributes, 100, FillStyle::WhiteNoise);
 
     //grid.add_emitter(smoke());
+    //grid.add_swarm_emitter(boid());
+    //grid.add_emitter(rocket());
     grid.add_emitter(another_emitter());
 
+    let mut renderer = MacroquadRenderer;
+
     //let color = Color::from_rgba(0, 26, 51, 255);
     loop {
         clear_background(BLACK);
 
-        grid.draw();
+        grid.step();
+        grid.draw(&mut renderer);
         grid.draw_ui();
 
         next_frame().await
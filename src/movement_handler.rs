@@ -1,66 +1,166 @@
 use crate::point::Point;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathMode {
+    /// Stops at the last point.
+    Once,
+    /// Flies from the last point back to the first one.
+    Loop,
+    /// Flies the path backwards after reaching the last point, and forwards again after the first.
+    PingPong,
+}
+
 // Moves emitter over the flight path.
 #[derive(Debug)]
 pub struct MovementHandler {
     flight_path: Vec<Point>,
-    /// Applied each frame
+    /// px/s
     speed: f32,
+    path_mode: PathMode,
+    /// px/s
     vx: f32,
+    /// px/s
     vy: f32,
     towards_point_index: usize,
+    backwards: bool,
+    finished: bool,
 }
 
 impl MovementHandler {
-    pub fn new(speed: f32) -> Self {
+    /// Flies from the current position to the first point and follows the path from there.
+    pub fn new(flight_path: Vec<Point>, speed: f32, path_mode: PathMode) -> Self {
         Self {
-            flight_path: Vec::new(),
+            finished: flight_path.is_empty(),
+            flight_path,
             towards_point_index: 0,
+            backwards: false,
             vx: 0.,
             vy: 0.,
             speed,
+            path_mode,
         }
     }
 
-    pub fn travel(&mut self, current_point: Point) -> Point {
-        let to_point = self.flight_path[self.towards_point_index];
-        let new_x = current_point.0 + self.vx;
-        let new_y = current_point.1 + self.vy;
-
-        let x_before = (to_point.0 - current_point.0).is_sign_positive();
-        let y_before = (to_point.1 - current_point.1).is_sign_positive();
-        let x_after = (to_point.0 - new_x).is_sign_positive();
-        let y_after = (to_point.1 - new_y).is_sign_positive();
-
-        if new_x == to_point.0 && new_y == to_point.1 {
-            self.change_direction(current_point)
-        } else if x_before == x_after && y_before == y_after {
-            Point(new_x, new_y)
-        } else {
-            self.vx = to_point.0 - new_x;
-            self.vy = to_point.1 - new_y;
-            to_point
+    /// Returns the point after traveling for dt seconds, can pass multiple points of the path.
+    pub fn travel(&mut self, current_point: Point, dt: f32) -> Point {
+        let mut point = current_point;
+        let mut distance = self.speed * dt;
+
+        // Prevents spinning forever on a path where every point is the same.
+        let mut hops = 0;
+
+        while !self.finished && hops <= self.flight_path.len() {
+            let to_point = self.flight_path[self.towards_point_index];
+            let dx = to_point.0 - point.0;
+            let dy = to_point.1 - point.1;
+            let remaining = (dx.powi(2) + dy.powi(2)).sqrt();
+
+            if remaining <= distance {
+                distance -= remaining;
+                point = to_point;
+                hops += 1;
+                self.change_direction(point);
+                continue;
+            }
+
+            self.vx = dx / remaining * self.speed;
+            self.vy = dy / remaining * self.speed;
+            point = Point(
+                point.0 + dx / remaining * distance,
+                point.1 + dy / remaining * distance,
+            );
+            break;
         }
+
+        point
     }
 
-    pub fn change_direction(&mut self, current_point: Point) -> Point {
-        if self.towards_point_index + 1 < self.flight_path.len() {
-            self.towards_point_index += 1;
-        } else {
-            self.towards_point_index = 0;
-        }
+    /// Heads towards the next point of the path.
+    pub fn change_direction(&mut self, current_point: Point) {
+        let last_index = self.flight_path.len() - 1;
+
+        match self.path_mode {
+            PathMode::Once if self.towards_point_index == last_index => {
+                self.finished = true;
+                self.vx = 0.;
+                self.vy = 0.;
+                return;
+            }
+            PathMode::Once => self.towards_point_index += 1,
+            PathMode::Loop if self.towards_point_index == last_index => {
+                self.towards_point_index = 0;
+            }
+            PathMode::Loop => self.towards_point_index += 1,
+            PathMode::PingPong => {
+                if last_index == 0 {
+                    return;
+                }
 
-        // Update vx, vy
-        let new_point = &mut self.flight_path[self.towards_point_index];
+                if self.towards_point_index == last_index {
+                    self.backwards = true;
+                } else if self.towards_point_index == 0 {
+                    self.backwards = false;
+                }
 
-        let x_abs = current_point.0 - new_point.0;
-        let y_abs = current_point.1 - new_point.1;
+                if self.backwards {
+                    self.towards_point_index -= 1;
+                } else {
+                    self.towards_point_index += 1;
+                }
+            }
+        }
 
-        let angle = y_abs / x_abs;
+        let new_point = self.flight_path[self.towards_point_index];
+        let angle = (new_point.1 - current_point.1).atan2(new_point.0 - current_point.0);
 
         self.vx = self.speed * angle.cos();
         self.vy = self.speed * angle.sin();
+    }
+
+    /// Travel direction in radians, 0 is to the right.
+    pub fn heading(&self) -> f32 {
+        self.vy.atan2(self.vx)
+    }
+
+    pub fn is_moving(&self) -> bool {
+        !self.finished && (self.vx != 0. || self.vy != 0.)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MovementHandler, PathMode};
+    use crate::point::Point;
+
+    fn path() -> Vec<Point> {
+        vec![Point(0., 0.), Point(100., 0.), Point(100., 100.)]
+    }
+
+    fn travel(handler: &mut MovementHandler, start: Point, seconds: u32) -> Point {
+        (0..seconds).fold(start, |point, _| handler.travel(point, 1.))
+    }
+
+    #[test]
+    fn follows_path_modes() {
+        let mut once = MovementHandler::new(path(), 50., PathMode::Once);
+        let point = travel(&mut once, Point(0., 0.), 3);
+        assert_eq!((100., 50.), (point.0, point.1));
+        assert!((once.heading() - std::f32::consts::FRAC_PI_2).abs() < 0.001);
+
+        let point = travel(&mut once, point, 10);
+        assert_eq!((100., 100.), (point.0, point.1));
+        assert!(!once.is_moving());
+
+        // Back towards the first point over the diagonal.
+        let mut looping = MovementHandler::new(path(), 50., PathMode::Loop);
+        let point = travel(&mut looping, Point(0., 0.), 6);
+        let point = looping.travel(point, 0.5);
+        assert!((point.0 - 11.61).abs() < 0.01);
+        assert!((point.0 - point.1).abs() < 0.01);
 
-        Point(current_point.0 + self.vx, current_point.1 + self.vy)
+        let mut ping_pong = MovementHandler::new(path(), 50., PathMode::PingPong);
+        let point = travel(&mut ping_pong, Point(0., 0.), 6);
+        assert_eq!((100., 0.), (point.0, point.1));
+        assert!(ping_pong.vx < 0.);
     }
 }
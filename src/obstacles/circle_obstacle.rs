@@ -0,0 +1,76 @@
+use crate::obstacles::obstacle::{Contact, Obstacle};
+use crate::position::Position;
+use crate::renderer::Renderer;
+use macroquad::prelude::Color;
+
+pub struct CircleObstacle {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub color: Color,
+}
+
+impl Obstacle for CircleObstacle {
+    fn contact(&self, x: f32, y: f32, radius: f32) -> Option<Contact> {
+        let dx = x - self.x;
+        let dy = y - self.y;
+        let min_distance = self.radius + radius;
+        let distance_pow = dx.powi(2) + dy.powi(2);
+
+        if min_distance.powi(2) <= distance_pow {
+            return None;
+        }
+
+        let distance = distance_pow.sqrt();
+
+        // On the center, any direction will do.
+        let (nx, ny) = if distance == 0. {
+            (0., -1.)
+        } else {
+            (dx / distance, dy / distance)
+        };
+
+        Some(Contact {
+            nx,
+            ny,
+            depth: min_distance - distance,
+        })
+    }
+
+    fn draw(&self, renderer: &mut dyn Renderer, grid_position: &Position) {
+        let x = self.x + grid_position.x;
+        let y = self.y + grid_position.y;
+
+        renderer.draw_circle(x, y, self.radius, self.color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CircleObstacle;
+    use crate::obstacles::obstacle::Obstacle;
+    use macroquad::prelude::Color;
+
+    #[test]
+    fn pushes_away_from_center() {
+        let circle = CircleObstacle {
+            x: 50.,
+            y: 50.,
+            radius: 10.,
+            color: Color::from_rgba(255, 255, 255, 255),
+        };
+
+        assert!(circle.contact(50., 63., 2.5).is_none());
+
+        // Overlapping on the right.
+        let contact = circle.contact(61., 50., 2.5).unwrap();
+        assert_eq!((1., 0., 1.5), (contact.nx, contact.ny, contact.depth));
+
+        // Below the circle.
+        let contact = circle.contact(50., 62., 2.5).unwrap();
+        assert_eq!((0., 1., 0.5), (contact.nx, contact.ny, contact.depth));
+
+        let contact = circle.contact(50., 50., 2.5).unwrap();
+        assert_eq!((0., -1., 12.5), (contact.nx, contact.ny, contact.depth));
+    }
+}
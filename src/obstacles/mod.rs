@@ -0,0 +1,4 @@
+pub mod circle_obstacle;
+pub mod obstacle;
+pub mod polygon_obstacle;
+pub mod segment_obstacle;
@@ -0,0 +1,110 @@
+use crate::collision::CollisionData;
+use crate::position::Position;
+use crate::renderer::Renderer;
+use std::fmt::Debug;
+
+/// Static shape in grid coordinates that grid and emitted particles bounce off of.
+pub trait Obstacle {
+    /// Returns the contact if a circle at x, y (center) overlaps the obstacle.
+    fn contact(&self, x: f32, y: f32, radius: f32) -> Option<Contact>;
+    fn draw(&self, renderer: &mut dyn Renderer, grid_position: &Position);
+}
+
+impl Debug for dyn Obstacle {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Obstacle")
+    }
+}
+
+pub struct Contact {
+    /// Unit normal pointing from the obstacle towards the particle.
+    pub nx: f32,
+    pub ny: f32,
+    /// Distance the particle has to move along the normal to stop touching.
+    pub depth: f32,
+}
+
+/**
+ * Moves the particle out of the obstacle and bounces it along the contact normal.
+ * Elasticity is applied on the normal and friction (fraction lost per second, between 0 and 1)
+ * removes a part of the tangential velocity while touching, dt in seconds.
+ */
+pub fn resolve_contact(data: &mut CollisionData, contact: &Contact, friction: f32, dt: f32) {
+    data.x += contact.nx * contact.depth;
+    data.y += contact.ny * contact.depth;
+
+    let normal_velocity = data.vx * contact.nx + data.vy * contact.ny;
+
+    // Already moving away.
+    if 0. <= normal_velocity {
+        return;
+    }
+
+    let tangent_vx = data.vx - normal_velocity * contact.nx;
+    let tangent_vy = data.vy - normal_velocity * contact.ny;
+    let bounce = -normal_velocity * data.elasticity;
+    let retained = (1. - friction).powf(dt);
+
+    data.vx = tangent_vx * retained + bounce * contact.nx;
+    data.vy = tangent_vy * retained + bounce * contact.ny;
+}
+
+/// Contact with the point px, py, e.g. the closest point on an edge.
+pub fn point_contact(x: f32, y: f32, radius: f32, px: f32, py: f32) -> Option<Contact> {
+    let dx = x - px;
+    let dy = y - py;
+    let distance_pow = dx.powi(2) + dy.powi(2);
+
+    if radius.powi(2) <= distance_pow || distance_pow == 0. {
+        return None;
+    }
+
+    let distance = distance_pow.sqrt();
+
+    Some(Contact {
+        nx: dx / distance,
+        ny: dy / distance,
+        depth: radius - distance,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{resolve_contact, Contact};
+    use crate::collision::CollisionData;
+
+    #[test]
+    fn bounces_with_elasticity_and_friction() {
+        let mut data = CollisionData {
+            x: 10.,
+            y: 9.,
+            vx: 100.,
+            vy: 50.,
+            radius: 2.5,
+            mass: 1.,
+            elasticity: 0.5,
+        };
+
+        // Floor below the particle.
+        let contact = Contact {
+            nx: 0.,
+            ny: -1.,
+            depth: 1.,
+        };
+
+        resolve_contact(&mut data, &contact, 0.2, 1.);
+
+        assert_eq!(8., data.y);
+        assert_eq!(-25., data.vy);
+        assert_eq!(80., data.vx);
+
+        // Sliding for a second over many frames loses the same speed as one long frame.
+        data.vx = 100.;
+        for _ in 0..60 {
+            data.vy = 50.;
+            resolve_contact(&mut data, &contact, 0.2, 1. / 60.);
+        }
+
+        assert!((data.vx - 80.).abs() < 0.01);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::obstacles::obstacle::{point_contact, Contact, Obstacle};
+use crate::obstacles::segment_obstacle::closest_point;
+use crate::point::Point;
+use crate::position::Position;
+use crate::renderer::Renderer;
+use macroquad::prelude::Color;
+
+/// Closed polygon, convex or concave. The last point connects to the first one.
+pub struct PolygonObstacle {
+    pub points: Vec<Point>,
+    pub color: Color,
+}
+
+impl PolygonObstacle {
+    fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        let next = self.points.iter().cycle().skip(1);
+        self.points.iter().zip(next)
+    }
+}
+
+/// Even-odd rule, works for concave polygons. The last point connects to the first one.
+pub fn polygon_contains(points: &[Point], x: f32, y: f32) -> bool {
+    let mut inside = false;
+    let next = points.iter().cycle().skip(1);
+
+    for (start, end) in points.iter().zip(next) {
+        let crosses_y = (start.1 > y) != (end.1 > y);
+
+        if crosses_y && x < start.0 + (y - start.1) / (end.1 - start.1) * (end.0 - start.0) {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+impl Obstacle for PolygonObstacle {
+    fn contact(&self, x: f32, y: f32, radius: f32) -> Option<Contact> {
+        let closest = self
+            .edges()
+            .map(|(start, end)| closest_point(start, end, x, y))
+            .min_by(|a, b| {
+                let distance_a = (a.0 - x).powi(2) + (a.1 - y).powi(2);
+                let distance_b = (b.0 - x).powi(2) + (b.1 - y).powi(2);
+                distance_a.total_cmp(&distance_b)
+            })?;
+
+        if !polygon_contains(&self.points, x, y) {
+            return point_contact(x, y, radius, closest.0, closest.1);
+        }
+
+        // Center inside, pushes out through the closest edge.
+        let dx = closest.0 - x;
+        let dy = closest.1 - y;
+        let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+
+        if distance == 0. {
+            return None;
+        }
+
+        Some(Contact {
+            nx: dx / distance,
+            ny: dy / distance,
+            depth: distance + radius,
+        })
+    }
+
+    fn draw(&self, renderer: &mut dyn Renderer, grid_position: &Position) {
+        for (start, end) in self.edges() {
+            renderer.draw_line(
+                start.0 + grid_position.x,
+                start.1 + grid_position.y,
+                end.0 + grid_position.x,
+                end.1 + grid_position.y,
+                1.,
+                self.color,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PolygonObstacle;
+    use crate::obstacles::obstacle::Obstacle;
+    use crate::point::Point;
+    use macroquad::prelude::Color;
+
+    /// U shaped container, open at the top.
+    fn container() -> PolygonObstacle {
+        PolygonObstacle {
+            points: vec![
+                Point(0., 0.),
+                Point(10., 0.),
+                Point(10., 90.),
+                Point(90., 90.),
+                Point(90., 0.),
+                Point(100., 0.),
+                Point(100., 100.),
+                Point(0., 100.),
+            ],
+            color: Color::from_rgba(255, 255, 255, 255),
+        }
+    }
+
+    #[test]
+    fn touches_concave_polygon() {
+        let polygon = container();
+
+        // Inside the opening of the container.
+        assert!(polygon.contact(50., 50., 2.5).is_none());
+
+        // Resting on the floor of the container.
+        let contact = polygon.contact(50., 88., 2.5).unwrap();
+        assert_eq!(-1., contact.ny);
+        assert_eq!(0.5, contact.depth);
+
+        // Stuck in the left wall, pushed out to the left.
+        let contact = polygon.contact(2., 50., 2.5).unwrap();
+        assert_eq!(-1., contact.nx);
+        assert_eq!(4.5, contact.depth);
+    }
+}
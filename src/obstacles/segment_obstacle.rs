@@ -0,0 +1,93 @@
+use crate::obstacles::obstacle::{point_contact, Contact, Obstacle};
+use crate::point::Point;
+use crate::position::Position;
+use crate::renderer::Renderer;
+use macroquad::prelude::Color;
+
+pub struct SegmentObstacle {
+    pub start: Point,
+    pub end: Point,
+    pub thickness: f32,
+    pub color: Color,
+}
+
+impl Obstacle for SegmentObstacle {
+    fn contact(&self, x: f32, y: f32, radius: f32) -> Option<Contact> {
+        let Point(px, py) = closest_point(&self.start, &self.end, x, y);
+        let reach = radius + self.thickness / 2.;
+
+        point_contact(x, y, reach, px, py).or_else(|| {
+            if px != x || py != y {
+                return None;
+            }
+
+            // Exactly on the line, pushes out along its normal.
+            let dx = self.end.0 - self.start.0;
+            let dy = self.end.1 - self.start.1;
+            let length = (dx.powi(2) + dy.powi(2)).sqrt();
+
+            // Zero length segment, any direction will do.
+            let (nx, ny) = if length == 0. {
+                (0., -1.)
+            } else {
+                (-dy / length, dx / length)
+            };
+
+            Some(Contact {
+                nx,
+                ny,
+                depth: reach,
+            })
+        })
+    }
+
+    fn draw(&self, renderer: &mut dyn Renderer, grid_position: &Position) {
+        renderer.draw_line(
+            self.start.0 + grid_position.x,
+            self.start.1 + grid_position.y,
+            self.end.0 + grid_position.x,
+            self.end.1 + grid_position.y,
+            self.thickness,
+            self.color,
+        );
+    }
+}
+
+/// Point on the segment from start to end closest to x, y.
+pub fn closest_point(start: &Point, end: &Point, x: f32, y: f32) -> Point {
+    let dx = end.0 - start.0;
+    let dy = end.1 - start.1;
+    let length_pow = dx.powi(2) + dy.powi(2);
+
+    if length_pow == 0. {
+        return *start;
+    }
+
+    let fraction = (((x - start.0) * dx + (y - start.1) * dy) / length_pow).clamp(0., 1.);
+
+    Point(start.0 + dx * fraction, start.1 + dy * fraction)
+}
+
+#[cfg(test)]
+mod test {
+    use super::SegmentObstacle;
+    use crate::obstacles::obstacle::Obstacle;
+    use crate::point::Point;
+    use macroquad::prelude::Color;
+
+    #[test]
+    fn pushes_off_zero_length_segment() {
+        let segment = SegmentObstacle {
+            start: Point(10., 10.),
+            end: Point(10., 10.),
+            thickness: 2.,
+            color: Color::from_rgba(255, 255, 255, 255),
+        };
+
+        let contact = segment.contact(10., 10., 2.5).unwrap();
+        assert_eq!((0., -1., 3.5), (contact.nx, contact.ny, contact.depth));
+
+        let contact = segment.contact(13., 10., 2.5).unwrap();
+        assert_eq!((1., 0., 0.5), (contact.nx, contact.ny, contact.depth));
+    }
+}
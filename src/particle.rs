@@ -1,21 +1,30 @@
 use crate::animations::animation::AnimationData;
 use crate::animations::animation_handler::AnimationHandler;
 use crate::animations::animation_handler::AnimationOptions;
-use crate::collision::CollisionData;
+use crate::collision::{resolve_collision, resolve_impact, CollisionData};
+use crate::emitters::sub_emitter::{SubEmitter, SubEmitterData};
+use crate::grid::WALL_MARGIN;
+use crate::obstacles::obstacle::{resolve_contact, Obstacle};
+use crate::renderer::Renderer;
+use crate::rng::Rng;
+use crate::slot_map::Id;
 use crate::trails::trail_animation::TrailData;
 use crate::trails::trail_handler::TrailHandler;
 use macroquad::prelude::*;
-use std::rc::Rc;
-use std::time::Instant;
+use std::time::Duration;
 
 use crate::position::Position;
 
 #[derive(Debug)]
 pub struct Particle {
-    pub queue_frame: u64,
+    pub id: Id,
+    /// Substep in which the particle was moved to a spot that still has to be updated.
+    pub queue_substep: u64,
     pub x: f32,
     pub y: f32,
+    /// px/s
     pub vx: f32,
+    /// px/s
     pub vy: f32,
     pub radius: f32,
     pub diameter: f32,
@@ -24,17 +33,25 @@ pub struct Particle {
     pub mass: f32,
     /// number between 0 and 1.
     pub elasticity: f32,
-    /// number between 0 and 1. E.g. 0.008
+    /// Fraction of the velocity lost per second, number between 0 and 1. E.g. 0.4
     pub friction_coefficient: f32,
-    pub lifetime: Rc<Instant>,
+    /// Clock time at which the particle was spawned.
+    pub spawned_at: Duration,
+    /// Lives forever if None.
+    pub max_lifetime: Option<Duration>,
+    fade_out: Duration,
+    /// Multiplies the alpha of the color when drawing, drops to 0 during the fade out.
+    pub opacity: f32,
+    pub continuous_collision: bool,
     pub animation_handler: Option<AnimationHandler>,
     pub trail_handler: Option<TrailHandler>,
+    pub sub_emitters: Vec<SubEmitter>,
 }
 
 pub struct ParticleAttributes {
     /// number between 0 and 1. (percentage of bounciness).
     pub elasticity: f32,
-    /// number between 0 and 1. E.g. 0.008
+    /// Fraction of the velocity lost per second, number between 0 and 1. E.g. 0.4
     pub friction_coefficient: f32,
 
     pub color: Color,
@@ -43,13 +60,29 @@ pub struct ParticleAttributes {
     pub diameter: f32,
     pub animation_options: Option<AnimationOptions>,
     pub trail_handler: Option<TrailHandler>,
+    /// Sweeps the movement of every step so fast particles (sparks) can't pass through other
+    /// particles or walls. Costs more than the regular collision check.
+    pub continuous_collision: bool,
+    /// Lives forever if None. Animation tracks over the lifetime only play on particles with a max lifetime.
+    pub max_lifetime: Option<Duration>,
+    /// Fades out over the last part of the max lifetime instead of disappearing at once.
+    pub fade_out: Duration,
+    pub sub_emitters: Vec<SubEmitter>,
 }
 
 impl Particle {
-    pub fn new(x: f32, y: f32, attributes: &ParticleAttributes, lifetime: Rc<Instant>) -> Self {
-        let animation_handler = AnimationHandler::new(&attributes.animation_options);
+    pub fn new(
+        id: Id,
+        x: f32,
+        y: f32,
+        attributes: &ParticleAttributes,
+        spawned_at: Duration,
+        rng: &Rng,
+    ) -> Self {
+        let animation_handler = AnimationHandler::new(&attributes.animation_options, rng);
 
         Self {
+            id,
             x,
             y,
             vx: 0.,
@@ -61,142 +94,106 @@ impl Particle {
             diameter: attributes.diameter,
             elasticity: attributes.elasticity,
             mass: attributes.mass,
-            queue_frame: u64::MAX,
-            lifetime,
+            queue_substep: u64::MAX,
+            spawned_at,
+            max_lifetime: attributes.max_lifetime,
+            fade_out: attributes.fade_out,
+            opacity: 1.,
+            continuous_collision: attributes.continuous_collision,
             trail_handler: attributes.trail_handler.clone(),
+            sub_emitters: attributes.sub_emitters.clone(),
             animation_handler,
         }
     }
 
-    fn move_if_overlaps(&mut self, other: &mut Particle) {
-        let new_x = self.x + self.vx;
-        let new_y = self.y + self.vy;
-        let other_new_x = other.x + other.vx;
-        let other_new_y = other.y + other.vy;
-        let end_other_new_x = other_new_x + other.diameter;
-        let end_other_new_y = other_new_y + other.diameter;
-
-        let x_difference = new_x - other_new_x;
-        let y_difference = new_y - other_new_y;
-
-        let collision_self_placed_right = 0. < x_difference && x_difference < other.diameter;
-        let collision_self_placed_left = -self.diameter < x_difference && x_difference < 0.;
-        let collision_self_placed_bottom = 0. < y_difference && y_difference < other.diameter;
-        let collision_self_placed_top = -self.diameter < y_difference && y_difference < 0.;
-
-        let x_collision = collision_self_placed_left || collision_self_placed_right;
-        let y_collision = collision_self_placed_top || collision_self_placed_bottom;
-
-        if !x_collision || !y_collision {
-            return;
+    pub fn collision_data(&self) -> CollisionData {
+        CollisionData {
+            x: self.x + self.radius,
+            y: self.y + self.radius,
+            vx: self.vx,
+            vy: self.vy,
+            radius: self.radius,
+            mass: self.mass,
+            elasticity: self.elasticity,
         }
+    }
 
-        // The higher the difference the lower the overlap is.
-        // In case of overlap, you move back the least overlapping part.
-        // e.g. both particles diameter is 5.
-        // p1,x == 9, p2,x == 5. Remainder == 9 - 5. (4)
-        // p1,y == 8, p2,y == 5. Remainder == 8 - 5. (3)
-        // if x1 moves to the right with 1x its out of bound instead of y1 moving with 2y.
-        let move_back_horizontally = y_difference.abs() < x_difference.abs();
-
-        if move_back_horizontally {
-            if collision_self_placed_right {
-                if 0. <= self.vx {
-                    self.x = end_other_new_x - self.vx + 0.01;
-                } else {
-                    self.x = end_other_new_x + self.vx + 0.01;
-                }
-            } else if 0. <= other.vx {
-                self.x = other_new_x - self.diameter - other.vx - 0.01;
-            } else {
-                self.x = other_new_x - self.diameter + other.vx - 0.01;
-            }
-        } else {
-            if collision_self_placed_bottom {
-                if 0. <= self.vy {
-                    self.y = end_other_new_y - self.vy + 0.01;
-                } else {
-                    self.y = end_other_new_y + self.vy + 0.01;
-                }
-            } else if 0. <= other.vy {
-                self.y = other_new_y - self.diameter - other.vy - 0.01;
-            } else {
-                self.y = other_new_y - self.diameter + other.vy - 0.01;
-            }
-        }
+    pub fn apply_collision_data(&mut self, data: &CollisionData) {
+        self.x = data.x - self.radius;
+        self.y = data.y - self.radius;
+        self.vx = data.vx;
+        self.vy = data.vy;
     }
 
-    pub fn handle_possible_collision(
-        &mut self,
-        other: &mut Particle,
-        data: &mut CollisionData,
-    ) -> bool {
-        let CollisionData {
-            new_x,
-            new_y,
-            end_new_x,
-            end_new_y,
-        } = *data;
-
-        let other_new_x = other.x + other.vx;
-        let other_new_y = other.y + other.vy;
-        let end_other_new_x = other_new_x + other.diameter;
-        let end_other_new_y = other_new_y + other.diameter;
-
-        let inside_x = other_new_x <= new_x && new_x <= end_other_new_x
-            || other_new_x <= end_new_x && end_new_x <= end_other_new_x;
-        let inside_y = other_new_y <= new_y && new_y <= end_other_new_y
-            || other_new_y <= end_new_y && end_new_y <= end_other_new_y;
-
-        if !inside_x || !inside_y {
-            return false; // No collision
+    pub fn sub_emitter_data(&self) -> SubEmitterData {
+        SubEmitterData {
+            x: self.x + self.radius,
+            y: self.y + self.radius,
+            vx: self.vx,
+            vy: self.vy,
+            color: self.color,
         }
+    }
 
-        if self.mass == other.mass {
-            let tmp = self.vx;
-            self.vx = other.vx * self.elasticity;
-            other.vx = tmp * other.elasticity;
+    /// Returns true if the particles collided.
+    pub fn handle_possible_collision(&mut self, other: &mut Particle) -> bool {
+        let mut data = self.collision_data();
+        let mut other_data = other.collision_data();
 
-            let tmp = self.vy;
-            self.vy = other.vy * self.elasticity;
-            other.vy = tmp * other.elasticity;
-            self.move_if_overlaps(other);
-            return true;
+        if !resolve_collision(&mut data, &mut other_data) {
+            return false;
         }
 
-        let total_weight = self.mass + other.mass;
+        self.apply_collision_data(&data);
+        other.apply_collision_data(&other_data);
+        true
+    }
+
+    /// Bounces off a particle it touches, used after sweeping into it.
+    pub fn handle_impact(&mut self, other: &mut Particle) {
+        let mut data = self.collision_data();
+        let mut other_data = other.collision_data();
 
-        let transform_vx = ((self.mass - other.mass) / total_weight * self.vx)
-            + (2. * other.mass / total_weight * other.vx);
-        let other_vx = (2. * self.mass / total_weight * self.vx)
-            + ((other.mass - self.mass) / total_weight * other.vx);
+        resolve_impact(&mut data, &mut other_data);
 
-        self.vx = transform_vx * self.elasticity;
-        other.vx = other_vx * other.elasticity;
+        self.apply_collision_data(&data);
+        other.apply_collision_data(&other_data);
+    }
 
-        let transform_vy = ((self.mass - other.mass) / total_weight * self.vy)
-            + (2. * other.mass / total_weight * other.vy);
-        let other_vy = (2. * self.mass / total_weight * self.vy)
-            + ((other.mass - self.mass) / total_weight * other.vy);
+    /// Bounces off the obstacle, dt in seconds scales the friction.
+    /// Returns true if the particle touched the obstacle.
+    pub fn handle_obstacle(&mut self, obstacle: &dyn Obstacle, dt: f32) -> bool {
+        let mut data = self.collision_data();
 
-        self.vy = transform_vy * self.elasticity;
-        other.vx = other_vy * other.elasticity;
+        let Some(contact) = obstacle.contact(data.x, data.y, data.radius) else {
+            return false;
+        };
 
-        self.move_if_overlaps(other);
+        resolve_contact(&mut data, &contact, self.friction_coefficient, dt);
+        self.apply_collision_data(&data);
         true
     }
 
-    pub fn animate(&mut self) {
+    pub fn has_expired(&self, now: Duration) -> bool {
+        self.max_lifetime
+            .is_some_and(|max_lifetime| self.spawned_at + max_lifetime <= now)
+    }
+
+    pub fn animate(&mut self, now: Duration, rng: &Rng) {
+        self.fade(now);
+
         if let Some(animator) = &mut self.animation_handler {
             let mut data = AnimationData {
                 color: self.color,
                 radius: self.radius,
                 vx: self.vx,
                 vy: self.vy,
+                start_color: None,
             };
 
-            let elapsed_ms = self.lifetime.elapsed().as_millis();
-            animator.animate(&mut data, elapsed_ms);
+            let elapsed_ms = now.saturating_sub(self.spawned_at).as_millis();
+            let lifetime_ms = self.max_lifetime.map(|lifetime| lifetime.as_millis());
+            animator.animate(&mut data, elapsed_ms, lifetime_ms, rng);
 
             self.color = data.color;
             self.radius = data.radius;
@@ -204,60 +201,73 @@ impl Particle {
         }
     }
 
-    pub fn draw(&mut self, grid_position: &Position) {
-        let x = self.x + grid_position.x;
-        let y = self.y + grid_position.y;
+    fn fade(&mut self, now: Duration) {
+        let Some(max_lifetime) = self.max_lifetime else {
+            return;
+        };
+
+        let remaining = (self.spawned_at + max_lifetime).saturating_sub(now);
 
+        if remaining < self.fade_out {
+            self.opacity = remaining.as_secs_f32() / self.fade_out.as_secs_f32();
+        }
+    }
+
+    pub fn update_trail(&mut self, now: Duration) {
         if let Some(trail_handler) = &mut self.trail_handler {
-            let elapsed_ms = self.lifetime.elapsed().as_millis();
+            let elapsed_ms = now.saturating_sub(self.spawned_at).as_millis();
 
             let data = TrailData {
                 radius: self.radius,
                 color: self.color,
-                x_abs: x,
-                y_abs: y,
+                x: self.x,
+                y: self.y,
             };
 
             trail_handler.animate(&data, elapsed_ms);
         }
+    }
 
-        if let Some(texture) = self.texture {
-            let side = self.diameter;
-            let dest_size = Some(Vec2::new(side, side));
+    pub fn draw(&self, renderer: &mut dyn Renderer, grid_position: &Position) {
+        let x = self.x + grid_position.x;
+        let y = self.y + grid_position.y;
 
-            let params = DrawTextureParams {
-                dest_size,
-                ..Default::default()
-            };
+        if let Some(trail_handler) = &self.trail_handler {
+            trail_handler.draw(renderer, grid_position);
+        }
+
+        let mut color = self.color;
+        color.a *= self.opacity;
 
-            draw_texture_ex(texture, x, y, self.color, params);
+        if let Some(texture) = self.texture {
+            renderer.draw_texture(texture, x, y, self.diameter, color);
         } else {
-            draw_circle(x, y, self.radius, self.color);
+            renderer.draw_circle(x + self.radius, y + self.radius, self.radius, color);
         }
     }
 
-    pub fn apply_friction(&mut self) {
-        let x_loss = self.vx * self.mass * self.friction_coefficient;
-        let y_loss = self.vy * self.mass * self.friction_coefficient;
+    pub fn apply_friction(&mut self, dt: f32) {
+        let retained = (1. - self.friction_coefficient).powf(dt);
 
-        self.vx -= x_loss / self.mass;
-        self.vy -= y_loss / self.mass;
+        self.vx *= retained;
+        self.vy *= retained;
     }
 
-    pub fn transform(&mut self, max_width: f32, max_height: f32) {
-        self.x += self.vx;
-        self.y += self.vy;
+    /// Moves to x, y while staying inside the grid.
+    pub fn transform(&mut self, x: f32, y: f32, max_width: f32, max_height: f32) {
+        self.x = x;
+        self.y = y;
 
         if self.x < 0. {
             self.x = 0.;
         } else if max_width <= self.x + self.diameter {
-            self.x = max_width - 0.1 - self.diameter;
+            self.x = max_width - WALL_MARGIN - self.diameter;
         }
 
         if self.y < 0. {
             self.y = 0.;
         } else if max_height <= self.y + self.diameter {
-            self.y = max_height - 0.1 - self.diameter;
+            self.y = max_height - WALL_MARGIN - self.diameter;
         }
     }
 }
@@ -0,0 +1,67 @@
+use crate::emitters::emitter::EmittedParticle;
+use crate::particle::Particle;
+use crate::point::Point;
+use crate::slot_map::Id;
+use macroquad::prelude::Color;
+
+/**
+ * Refers to a grid or emitted particle for as long as it lives, no matter how often it moves
+ * between spots. Handles of removed particles don't refer to any particle anymore.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParticleHandle {
+    Grid(Id),
+    Emitted { emitter: Id, particle: Id },
+}
+
+/// Where a particle is stored right now, only valid until particles move or get removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleLocation {
+    Grid {
+        vec_index: usize,
+        spot_index: usize,
+    },
+    Emitted {
+        emitter_index: usize,
+        particle_index: usize,
+    },
+}
+
+#[derive(Debug)]
+pub enum ParticleRef<'a> {
+    Grid(&'a Particle),
+    Emitted(&'a EmittedParticle),
+}
+
+impl ParticleRef<'_> {
+    pub fn center(&self) -> Point {
+        match self {
+            Self::Grid(particle) => {
+                Point(particle.x + particle.radius, particle.y + particle.radius)
+            }
+            Self::Emitted(particle) => Point(particle.x, particle.y),
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        match self {
+            Self::Grid(particle) => particle.radius,
+            Self::Emitted(particle) => particle.radius,
+        }
+    }
+
+    /// px/s
+    pub fn velocity(&self) -> Point {
+        match self {
+            Self::Grid(particle) => Point(particle.vx, particle.vy),
+            Self::Emitted(particle) => Point(particle.vx, particle.vy),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Grid(particle) => particle.color,
+            Self::Emitted(particle) => particle.color,
+        }
+    }
+}
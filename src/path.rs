@@ -0,0 +1,243 @@
+use crate::point::Point;
+
+/// Samples per curve in the arc length table.
+const SAMPLES: usize = 64;
+
+#[derive(Debug, Clone)]
+pub enum PathShape {
+    /// Straight lines between the points.
+    Polyline(Vec<Point>),
+    /// Smooth curve passing through every point.
+    CatmullRom(Vec<Point>),
+    /// Curve from start to end, pulled towards both control points.
+    CubicBezier {
+        start: Point,
+        control_1: Point,
+        control_2: Point,
+        end: Point,
+    },
+    /// Part of a circle, 0 degrees is to the right and positive degrees turn clockwise.
+    Arc {
+        center: Point,
+        radius: f32,
+        from_degrees: f32,
+        to_degrees: f32,
+    },
+}
+
+/**
+ * Curve parametrised by arc length, so equal steps over the path cover equal distances no matter
+ * how the points of the shape are spread out.
+ */
+#[derive(Debug, Clone)]
+pub struct Path {
+    shape: PathShape,
+    /// Length of the path up to every sample.
+    lengths: Vec<f32>,
+}
+
+impl Path {
+    pub fn new(shape: PathShape) -> Self {
+        let sample_count = SAMPLES * curve_count(&shape);
+        let mut lengths = Vec::with_capacity(sample_count + 1);
+        let mut previous = shape_point(&shape, 0.);
+        let mut length = 0.;
+        lengths.push(length);
+
+        for sample in 1..=sample_count {
+            let point = shape_point(&shape, sample as f32 / sample_count as f32);
+            length += ((point.0 - previous.0).powi(2) + (point.1 - previous.1).powi(2)).sqrt();
+            lengths.push(length);
+            previous = point;
+        }
+
+        Self { shape, lengths }
+    }
+
+    /// Straight line from start to end.
+    pub fn line(start: Point, end: Point) -> Self {
+        Self::new(PathShape::Polyline(vec![start, end]))
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.)
+    }
+
+    /// Point at the fraction (0 to 1) of the length of the path.
+    pub fn point_at(&self, fraction: f32) -> Point {
+        let target = fraction.clamp(0., 1.) * self.length();
+        let index = self.lengths.partition_point(|length| *length < target);
+
+        if index == 0 || self.length() == 0. {
+            return shape_point(&self.shape, 0.);
+        }
+
+        let before = self.lengths[index - 1];
+        let after = self.lengths[index];
+        let between = if after == before {
+            0.
+        } else {
+            (target - before) / (after - before)
+        };
+
+        let sample_count = self.lengths.len() - 1;
+        let t = (index as f32 - 1. + between) / sample_count as f32;
+        shape_point(&self.shape, t)
+    }
+
+    /// Point at the fraction of the time window, clamped to the start and end of the path.
+    pub fn point_at_ms(&self, cycle_ms: u128, from_ms: u128, until_ms: u128) -> Point {
+        let delta_current = cycle_ms.saturating_sub(from_ms);
+        let delta_end = until_ms.saturating_sub(from_ms).max(1);
+
+        self.point_at(delta_current as f32 / delta_end as f32)
+    }
+}
+
+fn curve_count(shape: &PathShape) -> usize {
+    match shape {
+        PathShape::Polyline(points) | PathShape::CatmullRom(points) => points.len().max(2) - 1,
+        PathShape::CubicBezier { .. } | PathShape::Arc { .. } => 1,
+    }
+}
+
+/// Point at parameter t (0 to 1), not spread evenly over the length.
+fn shape_point(shape: &PathShape, t: f32) -> Point {
+    match shape {
+        PathShape::Polyline(points) => {
+            let Some((index, local_t)) = locate(points.len(), t) else {
+                return points.first().copied().unwrap_or(Point(0., 0.));
+            };
+
+            lerp(&points[index], &points[index + 1], local_t)
+        }
+        PathShape::CatmullRom(points) => {
+            let Some((index, local_t)) = locate(points.len(), t) else {
+                return points.first().copied().unwrap_or(Point(0., 0.));
+            };
+
+            // The first and last points are repeated, so the curve starts and ends on them.
+            let p0 = points[index.saturating_sub(1)];
+            let p1 = points[index];
+            let p2 = points[index + 1];
+            let p3 = points[(index + 2).min(points.len() - 1)];
+
+            catmull_rom(p0, p1, p2, p3, local_t)
+        }
+        PathShape::CubicBezier {
+            start,
+            control_1,
+            control_2,
+            end,
+        } => {
+            let u = 1. - t;
+            let a = u.powi(3);
+            let b = 3. * u.powi(2) * t;
+            let c = 3. * u * t.powi(2);
+            let d = t.powi(3);
+
+            Point(
+                a * start.0 + b * control_1.0 + c * control_2.0 + d * end.0,
+                a * start.1 + b * control_1.1 + c * control_2.1 + d * end.1,
+            )
+        }
+        PathShape::Arc {
+            center,
+            radius,
+            from_degrees,
+            to_degrees,
+        } => {
+            let angle = (from_degrees + (to_degrees - from_degrees) * t).to_radians();
+            Point(
+                center.0 + radius * angle.cos(),
+                center.1 + radius * angle.sin(),
+            )
+        }
+    }
+}
+
+/// Index of the first point of the curve that contains t, and t within that curve.
+fn locate(point_count: usize, t: f32) -> Option<(usize, f32)> {
+    if point_count < 2 {
+        return None;
+    }
+
+    let curves = point_count - 1;
+    let scaled = t.clamp(0., 1.) * curves as f32;
+    let index = (scaled as usize).min(curves - 1);
+
+    Some((index, scaled - index as f32))
+}
+
+fn lerp(from: &Point, to: &Point, t: f32) -> Point {
+    Point(from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+}
+
+fn catmull_rom(p0: Point, p1: Point, p2: Point, p3: Point, t: f32) -> Point {
+    let t2 = t.powi(2);
+    let t3 = t.powi(3);
+
+    let axis = |v0: f32, v1: f32, v2: f32, v3: f32| {
+        0.5 * (2. * v1
+            + (v2 - v0) * t
+            + (2. * v0 - 5. * v1 + 4. * v2 - v3) * t2
+            + (3. * v1 - v0 - 3. * v2 + v3) * t3)
+    };
+
+    Point(axis(p0.0, p1.0, p2.0, p3.0), axis(p0.1, p1.1, p2.1, p3.1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Path, PathShape};
+    use crate::point::Point;
+
+    fn distance(a: Point, b: Point) -> f32 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn parametrises_by_length() {
+        // The first line is 10 times shorter than the second one.
+        let path = Path::new(PathShape::Polyline(vec![
+            Point(0., 0.),
+            Point(10., 0.),
+            Point(10., 100.),
+        ]));
+
+        assert!((path.length() - 110.).abs() < 0.01);
+        assert!(distance(path.point_at(0.5), Point(10., 45.)) < 0.01);
+        assert!(distance(path.point_at(1.), Point(10., 100.)) < 0.01);
+    }
+
+    #[test]
+    fn passes_through_points() {
+        let catmull_rom = Path::new(PathShape::CatmullRom(vec![
+            Point(0., 0.),
+            Point(50., 50.),
+            Point(100., 0.),
+        ]));
+
+        assert!(distance(catmull_rom.point_at(0.5), Point(50., 50.)) < 0.5);
+        assert!(distance(catmull_rom.point_at(1.), Point(100., 0.)) < 0.01);
+
+        let arc = Path::new(PathShape::Arc {
+            center: Point(0., 0.),
+            radius: 10.,
+            from_degrees: 0.,
+            to_degrees: 180.,
+        });
+
+        assert!((arc.length() - 10. * std::f32::consts::PI).abs() < 0.01);
+        assert!(distance(arc.point_at(0.5), Point(0., 10.)) < 0.01);
+
+        let bezier = Path::new(PathShape::CubicBezier {
+            start: Point(0., 0.),
+            control_1: Point(0., 100.),
+            control_2: Point(100., 100.),
+            end: Point(100., 0.),
+        });
+
+        assert!(distance(bezier.point_at(0.5), Point(50., 75.)) < 0.01);
+    }
+}
@@ -3,22 +3,31 @@ use crate::animations::animation_handler::AnimationOptions;
 use crate::animations::animation_handler::StartAnimationAt;
 use crate::animations::color_animation::DuoColorAnimation;
 use crate::animations::color_animation::MonoColorAnimation;
+use crate::animations::gradient_animation::GradientAnimation;
 use crate::animations::size_animation::SizeAnimation;
 use crate::animations::stray_animation::StrayAnimation;
+use crate::boundary::{Boundaries, BoundaryMode};
+use crate::easing::Easing;
 use crate::emitters::diffusion_animation::DiffusionAnimation;
-use crate::emitters::emit_color_animation::EmitColorAnimation;
+use crate::emitters::emit_gradient_animation::EmitGradientAnimation;
 use crate::emitters::emit_speed_animation::EmitSpeedAnimation;
 use crate::emitters::emitter::EmitterOptions;
 use crate::emitters::emitter_animation::EmitterAnimate;
 use crate::emitters::emitter_animation_handler::EmitterAnimationHandler;
+use crate::emitters::emitter_shape::EmitterShape;
 use crate::emitters::loose_movement_animation::LooseMovementAnimation;
 use crate::emitters::randomize_size_animation::RandomizeSizeAnimation;
+use crate::emitters::sub_emitter::{SubEmitter, SubEmitterTrigger};
 use crate::emitters::sway_animation::SwayAnimation;
 use crate::force::accelerating_force::AcceleratingForce;
 use crate::force::constant_force::ConstantForce;
 use crate::force::force_handler::ForceHandler;
 use crate::force::gravitational_force::GravitationalForce;
+use crate::gradient::{ColorSpace, Gradient};
+use crate::movement_handler::{MovementHandler, PathMode};
+use crate::path::Path;
 use crate::point::Point;
+use crate::swarm_emitter::SwarmEmitterOptions;
 use crate::trails::trail_animation::TrailAnimation;
 use crate::trails::trail_animation::TrailOptions;
 use crate::trails::trail_handler::TrailHandler;
@@ -28,21 +37,22 @@ use crate::position::Position;
 use macroquad::prelude::*;
 
 pub fn shimmer_animations() -> AnimationOptions {
-    let mut animations: Vec<Box<dyn Animate>> = Vec::new();
-
-    animations.push(Box::new(DuoColorAnimation {
-        color_from: Color::from_rgba(255, 255, 255, 255),
-        color_to: Color::from_rgba(255, 255, 255, 0),
-        from_ms: 1000,
-        until_ms: 2000,
-    }));
-
-    animations.push(Box::new(DuoColorAnimation {
-        color_from: Color::from_rgba(255, 255, 255, 0),
-        color_to: Color::from_rgba(255, 255, 255, 255),
-        from_ms: 3000,
-        until_ms: 4000,
-    }));
+    let animations: Vec<Box<dyn Animate>> = vec![
+        Box::new(DuoColorAnimation {
+            color_from: Color::from_rgba(255, 255, 255, 255),
+            color_to: Color::from_rgba(255, 255, 255, 0),
+            from_ms: 1000,
+            until_ms: 2000,
+            easing: Easing::Linear,
+        }),
+        Box::new(DuoColorAnimation {
+            color_from: Color::from_rgba(255, 255, 255, 0),
+            color_to: Color::from_rgba(255, 255, 255, 255),
+            from_ms: 3000,
+            until_ms: 4000,
+            easing: Easing::Linear,
+        }),
+    ];
 
     AnimationOptions::new(4000, StartAnimationAt::RangeMs(0, 1000), animations)
 }
@@ -63,27 +73,28 @@ pub fn trail_animation() -> TrailHandler {
 }
 
 pub fn smoke() -> EmitterOptions {
-    let mut animations: Vec<Box<dyn Animate>> = Vec::new();
-
-    animations.push(Box::new(MonoColorAnimation {
-        color: Color::from_rgba(145, 42, 245, 255),
-        from_ms: 0,
-        until_ms: 1000,
-    }));
-
-    animations.push(Box::new(DuoColorAnimation {
-        color_to: Color::from_rgba(145, 42, 245, 255),
-        color_from: Color::from_rgba(200, 100, 1, 255),
-        from_ms: 1000,
-        until_ms: 2000,
-    }));
-
-    animations.push(Box::new(SizeAnimation {
-        from_ms: 0,
-        until_ms: 1000,
-        start_radius: 1.,
-        end_radius: 3.,
-    }));
+    let animations: Vec<Box<dyn Animate>> = vec![
+        Box::new(MonoColorAnimation {
+            color: Color::from_rgba(145, 42, 245, 255),
+            from_ms: 0,
+            until_ms: 1000,
+            easing: Easing::Linear,
+        }),
+        Box::new(DuoColorAnimation {
+            color_to: Color::from_rgba(145, 42, 245, 255),
+            color_from: Color::from_rgba(200, 100, 1, 255),
+            from_ms: 1000,
+            until_ms: 2000,
+            easing: Easing::Linear,
+        }),
+        Box::new(SizeAnimation {
+            from_ms: 0,
+            until_ms: 1000,
+            easing: Easing::Linear,
+            start_radius: 1.,
+            end_radius: 3.,
+        }),
+    ];
 
     let trail_anim_1 = TrailAnimation::new(TrailOptions {
         update_ms: 32,
@@ -105,24 +116,26 @@ pub fn smoke() -> EmitterOptions {
     force_handler.add(Box::new(ConstantForce {
         from_ms: 0,
         until_ms: 4000,
-        nx: 0.021,
-        ny: 0.02,
-        max_vx: 2.,
-        max_vy: 2.,
+        nx: 75.6,
+        ny: 72.,
+        max_vx: 120.,
+        max_vy: 120.,
     }));
 
     force_handler.add(Box::new(ConstantForce {
         from_ms: 2000,
         until_ms: 2700,
         nx: 0.,
-        ny: -0.03,
+        ny: -108.,
         max_vx: 0.,
-        max_vy: -2.,
+        max_vy: -120.,
     }));
 
     EmitterOptions {
         emitter_position: Position::new(300., 300.),
         emitter_diameter: 100.,
+        emitter_shape: EmitterShape::Line,
+        emit_along_normal: false,
         emitter_duration: Duration::from_secs(10),
         angle_degrees: 135.,
         emission_distortion_px: 0.,
@@ -134,14 +147,21 @@ pub fn smoke() -> EmitterOptions {
         particle_lifetime: Duration::from_secs(3),
         particle_radius: 5.,
         particle_mass: 1.,
-        particle_speed: 2.2,
-        particle_friction_coefficient: 0.01,
-        respect_grid_bounds: true,
+        particle_elasticity: 1.,
+        particle_speed: 132.,
+        particle_friction_coefficient: 0.45,
+        boundaries: Boundaries::all(BoundaryMode::Kill),
         particle_animation_options: Some(animation_options),
         force_handler: Some(force_handler),
         emitter_animation_handler: None,
         trail_handler: Some(trail_handler),
         //trail_handler: None,
+        movement_handler: None,
+        align_emission_to_movement: false,
+        seed: None,
+        integrator: None,
+        collide_with_grid: false,
+        sub_emitters: Vec::new(),
     }
 }
 
@@ -149,6 +169,7 @@ fn sway_and_diffusion_animation() -> Option<EmitterAnimationHandler> {
     let sway_1 = Box::new(SwayAnimation {
         from_ms: 0,
         until_ms: 1000,
+        easing: Easing::Linear,
         start_angle_degrees: 135.,
         end_angle_degrees: 360.,
     });
@@ -156,6 +177,7 @@ fn sway_and_diffusion_animation() -> Option<EmitterAnimationHandler> {
     let sway_2 = Box::new(SwayAnimation {
         from_ms: 1000,
         until_ms: 2000,
+        easing: Easing::Linear,
         start_angle_degrees: 0.,
         end_angle_degrees: 135.,
     });
@@ -163,6 +185,7 @@ fn sway_and_diffusion_animation() -> Option<EmitterAnimationHandler> {
     let sway_3 = Box::new(SwayAnimation {
         from_ms: 2000,
         until_ms: 3000,
+        easing: Easing::Linear,
         start_angle_degrees: 135.,
         end_angle_degrees: 360.,
     });
@@ -170,6 +193,7 @@ fn sway_and_diffusion_animation() -> Option<EmitterAnimationHandler> {
     let sway_4 = Box::new(SwayAnimation {
         from_ms: 3000,
         until_ms: 4000,
+        easing: Easing::Linear,
         start_angle_degrees: 0.,
         end_angle_degrees: 135.,
     });
@@ -177,6 +201,7 @@ fn sway_and_diffusion_animation() -> Option<EmitterAnimationHandler> {
     let diffusion_1 = Box::new(DiffusionAnimation {
         from_ms: 0,
         until_ms: 2000,
+        easing: Easing::Linear,
         start_diffusion_degrees: 70.,
         end_diffusion_degrees: 5.,
     });
@@ -184,6 +209,7 @@ fn sway_and_diffusion_animation() -> Option<EmitterAnimationHandler> {
     let diffusion_2 = Box::new(DiffusionAnimation {
         from_ms: 2000,
         until_ms: 4000,
+        easing: Easing::Linear,
         start_diffusion_degrees: 5.,
         end_diffusion_degrees: 125.,
     });
@@ -191,38 +217,46 @@ fn sway_and_diffusion_animation() -> Option<EmitterAnimationHandler> {
     let movement_1 = Box::new(LooseMovementAnimation {
         from_ms: 0,
         until_ms: 3000,
-        vx: 1.1,
-        vy: 0.8,
+        vx: 66.,
+        vy: 48.,
         stray_radians: 2_f32.to_radians(),
     });
 
     let movement_2 = Box::new(LooseMovementAnimation {
         from_ms: 3000,
         until_ms: 4000,
-        vx: -1.2,
-        vy: -0.6,
+        vx: -72.,
+        vy: -36.,
         stray_radians: 2_f32.to_radians(),
     });
 
-    let color_1 = Box::new(EmitColorAnimation {
+    let color_1 = Box::new(EmitGradientAnimation {
         from_ms: 1000,
         until_ms: 3000,
-        from_color: Color::from_rgba(0, 100, 155, 255),
-        to_color: Color::from_rgba(0, 135, 100, 255),
+        easing: Easing::Linear,
+        gradient: Gradient::even(
+            vec![
+                Color::from_rgba(0, 100, 155, 255),
+                Color::from_rgba(0, 135, 100, 255),
+            ],
+            ColorSpace::Srgb,
+        ),
     });
 
     let speed_1 = Box::new(EmitSpeedAnimation {
         from_ms: 1000,
         until_ms: 2000,
-        from_speed: 2.5,
-        to_speed: 1.5,
+        easing: Easing::Linear,
+        from_speed: 150.,
+        to_speed: 90.,
     });
 
     let speed_2 = Box::new(EmitSpeedAnimation {
         from_ms: 3000,
         until_ms: 4000,
-        from_speed: 1.5,
-        to_speed: 4.0,
+        easing: Easing::Linear,
+        from_speed: 90.,
+        to_speed: 240.,
     });
 
     let randomize_size_1 = Box::new(RandomizeSizeAnimation {
@@ -249,23 +283,22 @@ fn sway_and_diffusion_animation() -> Option<EmitterAnimationHandler> {
 }
 
 pub fn another_emitter() -> EmitterOptions {
-    let mut animations: Vec<Box<dyn Animate>> = Vec::new();
-
-    animations.push(Box::new(DuoColorAnimation {
-        color_from: Color::from_rgba(0, 10, 20, 255),
-        color_to: Color::from_rgba(0, 61, 152, 255),
-        from_ms: 1000,
-        until_ms: 2000,
-    }));
-
-    animations.push(Box::new(DuoColorAnimation {
-        color_from: Color::from_rgba(0, 61, 162, 255),
-        color_to: Color::from_rgba(102, 0, 102, 255),
-        from_ms: 1000,
-        until_ms: 3_000,
-    }));
-
-    animations.push(Box::new(StrayAnimation::new(1_000, 3_000, 10.)));
+    let animations: Vec<Box<dyn Animate>> = vec![
+        Box::new(GradientAnimation {
+            gradient: Gradient::even(
+                vec![
+                    Color::from_rgba(0, 10, 20, 255),
+                    Color::from_rgba(0, 61, 152, 255),
+                    Color::from_rgba(102, 0, 102, 255),
+                ],
+                ColorSpace::OkLab,
+            ),
+            from_ms: 1000,
+            until_ms: 3_000,
+            easing: Easing::Linear,
+        }),
+        Box::new(StrayAnimation::new(1_000, 3_000, 10.)),
+    ];
 
     let animation_options = AnimationOptions::new(3_000, StartAnimationAt::Zero, animations);
 
@@ -286,26 +319,26 @@ pub fn another_emitter() -> EmitterOptions {
     force_handler.add(Box::new(GravitationalForce {
         from_ms: 0,
         until_ms: 5000,
-        gravitation_force: -0.3,
+        gravitation_force: -1080.,
         dead_zone: 30.,
         mass: 1000.,
-        start: Point(400., 400.),
-        end: Point(400., 800.),
+        path: Path::line(Point(400., 400.), Point(400., 800.)),
     }));
 
     force_handler.add(Box::new(GravitationalForce {
         from_ms: 5000,
         until_ms: 10000,
-        gravitation_force: -0.4,
+        gravitation_force: -1440.,
         dead_zone: 20.,
         mass: 1000.,
-        start: Point(400., 800.),
-        end: Point(400., 400.),
+        path: Path::line(Point(400., 800.), Point(400., 400.)),
     }));
 
     EmitterOptions {
         emitter_position: Position::new(300., 200.),
         emitter_diameter: 100.,
+        emitter_shape: EmitterShape::Line,
+        emit_along_normal: false,
         emitter_duration: Duration::from_secs(10),
         angle_degrees: 135.,
         emission_distortion_px: 3.,
@@ -317,13 +350,197 @@ pub fn another_emitter() -> EmitterOptions {
         particle_lifetime: Duration::from_secs(4),
         particle_radius: 3.,
         particle_mass: 1.,
-        particle_friction_coefficient: 0.007,
-        particle_speed: 2.5,
-        respect_grid_bounds: false,
+        particle_elasticity: 1.,
+        particle_friction_coefficient: 0.34,
+        particle_speed: 150.,
+        boundaries: Boundaries::all(BoundaryMode::Open),
         particle_animation_options: Some(animation_options),
         force_handler: Some(force_handler),
         emitter_animation_handler: sway_and_diffusion_animation(),
         trail_handler,
+        movement_handler: None,
+        align_emission_to_movement: false,
+        seed: None,
+        integrator: None,
+        collide_with_grid: false,
+        sub_emitters: Vec::new(),
+    }
+}
+
+/// Falls from the top edge of a 500 px wide grid, drifts through the sides and melts at the bottom.
+pub fn snow() -> EmitterOptions {
+    let mut force_handler = ForceHandler::new(Duration::from_secs(8));
+
+    force_handler.add(Box::new(ConstantForce {
+        from_ms: 0,
+        until_ms: 8000,
+        nx: 0.,
+        ny: 30.,
+        max_vx: 0.,
+        max_vy: 45.,
+    }));
+
+    force_handler.add(Box::new(ConstantForce {
+        from_ms: 2000,
+        until_ms: 5000,
+        nx: 40.,
+        ny: 0.,
+        max_vx: 60.,
+        max_vy: 0.,
+    }));
+
+    EmitterOptions {
+        emitter_position: Position::new(500., 0.),
+        emitter_diameter: 500.,
+        emitter_shape: EmitterShape::Line,
+        emit_along_normal: false,
+        emitter_duration: Duration::from_secs(600),
+        angle_degrees: 180.,
+        emission_distortion_px: 0.,
+        delay_between_emission: Duration::from_millis(100),
+        diffusion_degrees: 20.,
+        particle_color: Color::from_rgba(255, 255, 255, 220),
+        particle_texture: None,
+        particles_per_emission: 2,
+        particle_lifetime: Duration::from_secs(60),
+        particle_radius: 2.,
+        particle_mass: 1.,
+        particle_elasticity: 0.2,
+        particle_friction_coefficient: 0.1,
+        particle_speed: 20.,
+        boundaries: Boundaries {
+            left: BoundaryMode::Wrap,
+            right: BoundaryMode::Wrap,
+            top: BoundaryMode::Open,
+            bottom: BoundaryMode::Kill,
+        },
+        particle_animation_options: None,
+        force_handler: Some(force_handler),
+        emitter_animation_handler: None,
+        trail_handler: None,
+        movement_handler: None,
+        align_emission_to_movement: false,
+        seed: None,
+        integrator: None,
+        collide_with_grid: false,
+        sub_emitters: Vec::new(),
+    }
+}
+
+/// Flies a square with a tail of particles behind it.
+pub fn comet() -> EmitterOptions {
+    let flight_path = vec![
+        Point(100., 100.),
+        Point(400., 100.),
+        Point(400., 400.),
+        Point(100., 400.),
+    ];
+
+    EmitterOptions {
+        emitter_position: Position::new(100., 100.),
+        emitter_diameter: 0.,
+        emitter_shape: EmitterShape::Line,
+        emit_along_normal: false,
+        emitter_duration: Duration::from_secs(60),
+        angle_degrees: 180.,
+        emission_distortion_px: 2.,
+        delay_between_emission: Duration::from_millis(16),
+        diffusion_degrees: 15.,
+        particle_color: Color::from_rgba(255, 180, 40, 255),
+        particle_texture: None,
+        particles_per_emission: 3,
+        particle_lifetime: Duration::from_millis(800),
+        particle_radius: 2.,
+        particle_mass: 1.,
+        particle_elasticity: 1.,
+        particle_friction_coefficient: 0.6,
+        particle_speed: 60.,
+        boundaries: Boundaries::all(BoundaryMode::Open),
+        particle_animation_options: None,
+        force_handler: None,
+        emitter_animation_handler: None,
+        trail_handler: None,
+        movement_handler: Some(MovementHandler::new(flight_path, 150., PathMode::Loop)),
+        align_emission_to_movement: true,
+        seed: None,
+        integrator: None,
+        collide_with_grid: false,
+        sub_emitters: Vec::new(),
+    }
+}
+
+/// Shoots up rockets that burst into sparks of their own color when they burn out.
+pub fn rocket() -> EmitterOptions {
+    EmitterOptions {
+        emitter_position: Position::new(250., 480.),
+        emitter_diameter: 200.,
+        emitter_shape: EmitterShape::Line,
+        emit_along_normal: false,
+        emitter_duration: Duration::from_secs(30),
+        angle_degrees: 0.,
+        emission_distortion_px: 0.,
+        delay_between_emission: Duration::from_millis(700),
+        diffusion_degrees: 10.,
+        particle_color: Color::from_rgba(255, 120, 60, 255),
+        particle_texture: None,
+        particles_per_emission: 1,
+        particle_lifetime: Duration::from_millis(1200),
+        particle_radius: 2.,
+        particle_mass: 1.,
+        particle_elasticity: 1.,
+        particle_friction_coefficient: 0.3,
+        particle_speed: 300.,
+        boundaries: Boundaries::all(BoundaryMode::Kill),
+        particle_animation_options: None,
+        force_handler: None,
+        emitter_animation_handler: None,
+        trail_handler: None,
+        movement_handler: None,
+        align_emission_to_movement: false,
+        seed: None,
+        integrator: None,
+        collide_with_grid: false,
+        sub_emitters: vec![SubEmitter {
+            trigger: SubEmitterTrigger::Death,
+            options: firework_burst,
+            inherit_color: true,
+            inherit_velocity: 0.3,
+        }],
+    }
+}
+
+/// Single burst of sparks, spawned by the rocket.
+pub fn firework_burst() -> EmitterOptions {
+    EmitterOptions {
+        emitter_position: Position::new(0., 0.),
+        emitter_diameter: 0.,
+        emitter_shape: EmitterShape::Point,
+        emit_along_normal: true,
+        emitter_duration: Duration::from_millis(100),
+        angle_degrees: 0.,
+        emission_distortion_px: 0.,
+        delay_between_emission: Duration::from_secs(1),
+        diffusion_degrees: 0.,
+        particle_color: Color::from_rgba(255, 255, 255, 255),
+        particle_texture: None,
+        particles_per_emission: 60,
+        particle_lifetime: Duration::from_millis(900),
+        particle_radius: 1.5,
+        particle_mass: 1.,
+        particle_elasticity: 1.,
+        particle_friction_coefficient: 0.8,
+        particle_speed: 140.,
+        boundaries: Boundaries::all(BoundaryMode::Kill),
+        particle_animation_options: None,
+        force_handler: None,
+        emitter_animation_handler: None,
+        trail_handler: None,
+        movement_handler: None,
+        align_emission_to_movement: false,
+        seed: None,
+        integrator: None,
+        collide_with_grid: false,
+        sub_emitters: Vec::new(),
     }
 }
 
@@ -333,63 +550,66 @@ pub fn random_forces() -> Option<ForceHandler> {
     force_handler.add(Box::new(AcceleratingForce {
         from_ms: 0,
         until_ms: 1000,
-        nx: 0.11,
-        ny: 0.1,
-        max_vx: 2.,
-        max_vy: 2.,
+        nx: 396.,
+        ny: 360.,
+        max_vx: 120.,
+        max_vy: 120.,
     }));
 
     force_handler.add(Box::new(AcceleratingForce {
         from_ms: 2_000,
         until_ms: 3_000,
-        nx: 0.1,
-        ny: -0.11,
-        max_vx: -2.,
-        max_vy: -2.,
+        nx: 360.,
+        ny: -396.,
+        max_vx: -120.,
+        max_vy: -120.,
     }));
 
     force_handler.add(Box::new(AcceleratingForce {
         from_ms: 3_000,
         until_ms: 4_000,
-        nx: -0.1,
-        ny: -0.11,
-        max_vx: -2.,
-        max_vy: -2.,
+        nx: -360.,
+        ny: -396.,
+        max_vx: -120.,
+        max_vy: -120.,
     }));
 
     force_handler.add(Box::new(GravitationalForce {
         from_ms: 0,
         until_ms: 6000,
-        gravitation_force: 0.5,
+        gravitation_force: 1800.,
         dead_zone: 30.,
         mass: 1000.,
-        start: Point(200., 200.),
-        end: Point(1000., 1000.),
+        path: Path::line(Point(200., 200.), Point(1000., 1000.)),
     }));
 
     force_handler.add(Box::new(GravitationalForce {
         from_ms: 0,
         until_ms: 6000,
-        gravitation_force: 0.4,
+        gravitation_force: 1440.,
         dead_zone: 20.,
         mass: 1000.,
-        start: Point(100., 900.),
-        end: Point(100., 900.),
+        path: Path::line(Point(100., 900.), Point(100., 900.)),
     }));
 
     Some(force_handler)
 }
 
-pub fn boid() {
+pub fn boid() -> SwarmEmitterOptions {
     let flight_pattern = vec![Point(100., 400.), Point(400., 400.), Point(100., 100.)];
-    //let emitter = SwarmEmitter {
-    //boid_speed: 1.,
-    //boid_count: 100,
-    //boid_color: Color::from_rgba(0, 255, 0, 255),
-    //boid_radius: 5.,
-    //emission_delay_ms: 10,
-    //diffusion: 0.,
-    //flight_pattern,
-    //boids: Vec::new(),
-    //};
+
+    SwarmEmitterOptions {
+        boid_speed: 60.,
+        boid_count: 100,
+        boid_color: Color::from_rgba(0, 255, 0, 255),
+        boid_radius: 5.,
+        emission_delay_ms: 10,
+        diffusion: 0.,
+        flight_pattern,
+        duration: Duration::from_secs(60),
+        perception_radius: 40.,
+        separation: 4.,
+        alignment: 1.,
+        cohesion: 1.,
+    }
 }
@@ -0,0 +1,32 @@
+use macroquad::prelude::*;
+
+/// Draws the simulation state. The simulation itself never draws, so it can be stepped without a
+/// graphics context (unit tests, servers, batch tools).
+pub trait Renderer {
+    fn draw_circle(&mut self, x: f32, y: f32, radius: f32, color: Color);
+    /// Draws a square texture with the top left corner at x, y.
+    fn draw_texture(&mut self, texture: Texture2D, x: f32, y: f32, side: f32, color: Color);
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color);
+}
+
+/// Draws using macroquad, requires a GL context.
+pub struct MacroquadRenderer;
+
+impl Renderer for MacroquadRenderer {
+    fn draw_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        draw_circle(x, y, radius, color);
+    }
+
+    fn draw_texture(&mut self, texture: Texture2D, x: f32, y: f32, side: f32, color: Color) {
+        let params = DrawTextureParams {
+            dest_size: Some(Vec2::new(side, side)),
+            ..Default::default()
+        };
+
+        draw_texture_ex(texture, x, y, color, params);
+    }
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        draw_line(x1, y1, x2, y2, thickness, color);
+    }
+}
@@ -0,0 +1,84 @@
+use std::cell::Cell;
+
+const DEFAULT_INC: u64 = 1442695040888963407;
+const MULTIPLIER: u64 = 6364136223846793005;
+
+/// Seedable pseudo random number generator (PCG32, same algorithm as macroquad's global rand).
+/// Handed out by reference, so the same seed always results in the same simulation.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: Cell<u64>,
+}
+
+pub trait RandomRange {
+    fn gen_range(rng: &Rng, low: Self, high: Self) -> Self;
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let rng = Self {
+            state: Cell::new(0),
+        };
+
+        rng.rand();
+        rng.state.set(rng.state.get().wrapping_add(seed));
+        rng.rand();
+        rng
+    }
+
+    /// Returns a pseudo random number between 0 and u32::MAX.
+    pub fn rand(&self) -> u32 {
+        let old_state = self.state.get();
+        self.state
+            .set(old_state.wrapping_mul(MULTIPLIER).wrapping_add(DEFAULT_INC));
+
+        let xor_shifted = (((old_state >> 18) ^ old_state) >> 27) as u32;
+        let rotation = (old_state >> 59) as u32;
+        xor_shifted.rotate_right(rotation)
+    }
+
+    pub fn gen_range<T: RandomRange>(&self, low: T, high: T) -> T {
+        T::gen_range(self, low, high)
+    }
+
+    /// Returns a number between 0 and 1.
+    fn fraction(&self) -> f32 {
+        self.rand() as f32 / u32::MAX as f32
+    }
+}
+
+impl RandomRange for f32 {
+    fn gen_range(rng: &Rng, low: Self, high: Self) -> Self {
+        low + (high - low) * rng.fraction()
+    }
+}
+
+impl RandomRange for u32 {
+    /// Never returns high, low if the range is empty.
+    fn gen_range(rng: &Rng, low: Self, high: Self) -> Self {
+        // Scales with a widening multiply (Lemire), stays exact over the whole u32 range.
+        let range = high.saturating_sub(low) as u64;
+        low + ((rng.rand() as u64 * range) >> 32) as u32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rng;
+
+    #[test]
+    fn stays_within_half_open_range() {
+        let rng = Rng::new(1);
+        let mut hits = [0; 3];
+
+        for _ in 0..1000 {
+            let value = rng.gen_range(5_u32, 8);
+            assert!((5..8).contains(&value));
+            hits[(value - 5) as usize] += 1;
+        }
+
+        assert!(hits.iter().all(|&count| 0 < count));
+        assert_eq!(3, rng.gen_range(3_u32, 3));
+        assert_eq!(u32::MAX - 1, rng.gen_range(u32::MAX - 1, u32::MAX));
+    }
+}
@@ -0,0 +1,129 @@
+/**
+ * Index of a slot plus the generation of the value in it. A slot gets a new generation when its
+ * value is removed, so ids of removed values never refer to the value that reuses the slot.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Id {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Stores small values (e.g. where a particle lives) under stable ids, with O(1) lookup.
+#[derive(Debug)]
+pub struct SlotMap<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T: Copy> SlotMap<T> {
+    pub fn insert(&mut self, value: T) -> Id {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+
+            return Id {
+                index,
+                generation: slot.generation,
+            };
+        }
+
+        self.slots.push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+
+        Id {
+            index: self.slots.len() as u32 - 1,
+            generation: 0,
+        }
+    }
+
+    pub fn get(&self, id: Id) -> Option<T> {
+        self.slot(id)?.value
+    }
+
+    /// Replaces the value, does nothing if the id got removed.
+    pub fn set(&mut self, id: Id, value: T) {
+        if let Some(slot) = self.slot_mut(id) {
+            if slot.value.is_some() {
+                slot.value = Some(value);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let slot = self.slot_mut(id)?;
+        let value = slot.value.take()?;
+
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        Some(value)
+    }
+
+    /// Removes every value, none of the handed out ids stays valid.
+    pub fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32);
+            }
+        }
+    }
+
+    fn slot(&self, id: Id) -> Option<&Slot<T>> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+    }
+
+    fn slot_mut(&mut self, id: Id) -> Option<&mut Slot<T>> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SlotMap;
+
+    #[test]
+    fn reuses_slots_with_new_generation() {
+        let mut slots = SlotMap::default();
+        let a = slots.insert(1);
+        let b = slots.insert(2);
+
+        slots.set(b, 3);
+        assert_eq!(Some(3), slots.get(b));
+
+        assert_eq!(Some(1), slots.remove(a));
+        assert_eq!(None, slots.remove(a));
+
+        // Takes the slot of a, but a stays removed.
+        let c = slots.insert(4);
+        assert_eq!(None, slots.get(a));
+        assert_eq!(Some(4), slots.get(c));
+
+        slots.set(a, 5);
+        assert_eq!(Some(4), slots.get(c));
+
+        slots.clear();
+        assert_eq!(None, slots.get(b));
+        assert_eq!(None, slots.get(c));
+    }
+}
@@ -0,0 +1,10 @@
+/// How many times the grid particles are updated per grid update.
+#[derive(Debug, Clone, Copy)]
+pub enum Substeps {
+    Fixed(u32),
+    /// Splits the update so the fastest particle moves at most max_distance px per substep.
+    Adaptive {
+        max_distance: f32,
+        max_substeps: u32,
+    },
+}
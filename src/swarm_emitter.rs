@@ -1,25 +1,399 @@
+use std::rc::Rc;
 use std::time::Duration;
-use std::time::Instant;
 
+use crate::integrators::integrator::{IntegrationData, Integrator};
 use crate::point::Point;
+use crate::position::Position;
+use crate::renderer::Renderer;
+use crate::rng::Rng;
 use macroquad::prelude::Color;
 
-//
-pub struct SwarmEmitter {
+pub struct SwarmEmitterOptions {
+    /// px/s
     pub boid_speed: f32,
     pub boid_count: u32,
     pub boid_color: Color, // TODO choice between color or texture.
     pub boid_radius: f32,
     pub emission_delay_ms: u128,
+    /// Random spread of the initial heading in degrees.
     pub diffusion: f32,
+    /// Grid coordinates the leader flies along, loops back to the first point.
+    /// Boids are spawned on the first point.
     pub flight_pattern: Vec<Point>,
+    pub duration: Duration,
+    /// Boids within this distance are neighbours. Every boid aligns with and moves towards the
+    /// leader from any distance, so the swarm follows it.
+    pub perception_radius: f32,
+    /// Steering weights, how fast boids turn towards the desired velocity.
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+}
+
+/**
+ * Flock of boids following a leader. Boids steer away from close neighbours (separation),
+ * towards the average heading (alignment) and towards the average position (cohesion).
+ * Neighbours are found by checking every pair, meant for a few hundred boids at most.
+ */
+#[derive(Debug)]
+pub struct SwarmEmitter {
+    boid_speed: f32,
+    boid_count: u32,
+    boid_color: Color,
+    boid_radius: f32,
+    emission_delay_ms: u128,
+    diffusion_radians: f32,
+    flight_pattern: Vec<Point>,
+    perception_radius: f32,
+    separation: f32,
+    alignment: f32,
+    cohesion: f32,
     pub boids: Vec<Boid>, // 1st on is the leader
-    lifetime: Instant,
+    towards_point_index: usize,
+    /// Clock time at which the swarm was added.
+    created_at: Duration,
     duration: Duration,
+    grid_position: Position,
+    rng: Rc<Rng>,
+    integrator: Rc<dyn Integrator>,
+    pub delete: bool,
 }
 
+/// x and y are the center of the boid.
+#[derive(Debug, Clone, Copy)]
 pub struct Boid {
-    x: f32,
-    y: f32,
-    angle_degrees: f32,
+    pub x: f32,
+    pub y: f32,
+    /// px/s
+    pub vx: f32,
+    /// px/s
+    pub vy: f32,
+}
+
+impl SwarmEmitter {
+    pub fn new(
+        grid_position: Position,
+        options: SwarmEmitterOptions,
+        created_at: Duration,
+        rng: Rc<Rng>,
+        integrator: Rc<dyn Integrator>,
+    ) -> Self {
+        let SwarmEmitterOptions {
+            boid_speed,
+            boid_count,
+            boid_color,
+            boid_radius,
+            emission_delay_ms,
+            diffusion,
+            flight_pattern,
+            duration,
+            perception_radius,
+            separation,
+            alignment,
+            cohesion,
+        } = options;
+
+        Self {
+            boid_speed,
+            boid_count,
+            boid_color,
+            boid_radius,
+            emission_delay_ms,
+            diffusion_radians: diffusion.to_radians(),
+            flight_pattern,
+            perception_radius,
+            separation,
+            alignment,
+            cohesion,
+            boids: Vec::new(),
+            towards_point_index: 1,
+            created_at,
+            duration,
+            grid_position,
+            rng,
+            integrator,
+            delete: false,
+        }
+    }
+
+    /// dt in seconds.
+    pub fn step(&mut self, now: Duration, dt: f32) {
+        let elapsed = now.saturating_sub(self.created_at);
+
+        if self.duration < elapsed {
+            self.delete = true;
+            return;
+        }
+
+        let emissions = elapsed.as_millis() / self.emission_delay_ms.max(1) + 1;
+        let spawn_count = emissions.min(self.boid_count as u128) as usize;
+
+        while self.boids.len() < spawn_count {
+            let boid = self.create_boid();
+            self.boids.push(boid);
+        }
+
+        self.update_leader(dt);
+        self.update_followers(dt);
+    }
+
+    fn start_point(&self) -> Point {
+        self.flight_pattern
+            .first()
+            .copied()
+            .unwrap_or(Point(0., 0.))
+    }
+
+    fn target_point(&self) -> Point {
+        let index = self.towards_point_index % self.flight_pattern.len().max(1);
+        self.flight_pattern
+            .get(index)
+            .copied()
+            .unwrap_or(Point(0., 0.))
+    }
+
+    /// Flies straight towards the next point of the flight pattern.
+    fn update_leader(&mut self, dt: f32) {
+        let target = self.target_point();
+        let speed = self.boid_speed;
+        let Some(leader) = self.boids.first_mut() else {
+            return;
+        };
+
+        let dx = target.0 - leader.x;
+        let dy = target.1 - leader.y;
+        let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+
+        if distance <= speed * dt {
+            leader.x = target.0;
+            leader.y = target.1;
+            self.towards_point_index += 1;
+            return;
+        }
+
+        leader.vx = dx / distance * speed;
+        leader.vy = dy / distance * speed;
+        leader.x += leader.vx * dt;
+        leader.y += leader.vy * dt;
+    }
+
+    fn update_followers(&mut self, dt: f32) {
+        // Every boid steers based on the positions at the start of the step.
+        let boids = self.boids.clone();
+
+        for index in 1..self.boids.len() {
+            let (ax, ay) = self.steering(&boids, index);
+            let boid = &mut self.boids[index];
+
+            let mut data = IntegrationData {
+                x: boid.x,
+                y: boid.y,
+                vx: boid.vx,
+                vy: boid.vy,
+            };
+
+            self.integrator.integrate(&mut data, dt, &mut |_| (ax, ay));
+
+            let speed = (data.vx.powi(2) + data.vy.powi(2)).sqrt();
+            let retained = if self.boid_speed < speed {
+                self.boid_speed / speed
+            } else {
+                1.
+            };
+
+            boid.x = data.x;
+            boid.y = data.y;
+            boid.vx = data.vx * retained;
+            boid.vy = data.vy * retained;
+        }
+    }
+
+    /// Returns the acceleration in px/s² of the boid at index.
+    fn steering(&self, boids: &[Boid], index: usize) -> (f32, f32) {
+        let boid = boids[index];
+        let mut separation = Point(0., 0.);
+        let mut heading = Point(0., 0.);
+        let mut center = Point(0., 0.);
+        let mut neighbours = 0.;
+
+        for (other_index, other) in boids.iter().enumerate() {
+            let dx = boid.x - other.x;
+            let dy = boid.y - other.y;
+            let distance_pow = dx.powi(2) + dy.powi(2);
+            let in_range = distance_pow <= self.perception_radius.powi(2);
+
+            // The leader is followed from any distance, but only pushes away when it's close.
+            if other_index == index || (other_index != 0 && !in_range) {
+                continue;
+            }
+
+            /*
+             * Weighted by the inverse distance, so the closest neighbours decide the direction.
+             * steer normalizes the sum, the strength comes from the separation weight.
+             */
+            if in_range && 0. < distance_pow {
+                separation.0 += dx / distance_pow;
+                separation.1 += dy / distance_pow;
+            }
+
+            heading.0 += other.vx;
+            heading.1 += other.vy;
+            center.0 += other.x;
+            center.1 += other.y;
+            neighbours += 1.;
+        }
+
+        if neighbours == 0. {
+            return (0., 0.);
+        }
+
+        let separation = self.steer(separation.0, separation.1, &boid);
+        let alignment = self.steer(heading.0, heading.1, &boid);
+        let cohesion = self.steer(
+            center.0 / neighbours - boid.x,
+            center.1 / neighbours - boid.y,
+            &boid,
+        );
+
+        let ax = separation.0 * self.separation
+            + alignment.0 * self.alignment
+            + cohesion.0 * self.cohesion;
+        let ay = separation.1 * self.separation
+            + alignment.1 * self.alignment
+            + cohesion.1 * self.cohesion;
+
+        (ax, ay)
+    }
+
+    /// Difference between the current velocity and full speed in the direction of dx, dy.
+    fn steer(&self, dx: f32, dy: f32, boid: &Boid) -> Point {
+        let length = (dx.powi(2) + dy.powi(2)).sqrt();
+
+        if length == 0. {
+            return Point(0., 0.);
+        }
+
+        let desired_vx = dx / length * self.boid_speed;
+        let desired_vy = dy / length * self.boid_speed;
+
+        Point(desired_vx - boid.vx, desired_vy - boid.vy)
+    }
+
+    fn create_boid(&self) -> Boid {
+        let start = self.start_point();
+        let target = self.target_point();
+
+        let diffusion_delta = self
+            .rng
+            .gen_range(-self.diffusion_radians, self.diffusion_radians);
+        let angle_radians = (target.1 - start.1).atan2(target.0 - start.0) + diffusion_delta;
+
+        Boid {
+            x: start.0,
+            y: start.1,
+            vx: self.boid_speed * angle_radians.cos(),
+            vy: self.boid_speed * angle_radians.sin(),
+        }
+    }
+
+    pub fn draw(&self, renderer: &mut dyn Renderer) {
+        for boid in self.boids.iter() {
+            let x = boid.x + self.grid_position.x;
+            let y = boid.y + self.grid_position.y;
+
+            renderer.draw_circle(x, y, self.boid_radius, self.boid_color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Boid, SwarmEmitter, SwarmEmitterOptions};
+    use crate::integrators::semi_implicit_euler::SemiImplicitEuler;
+    use crate::point::Point;
+    use crate::position::Position;
+    use crate::rng::Rng;
+    use macroquad::prelude::Color;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    fn swarm() -> SwarmEmitter {
+        let options = SwarmEmitterOptions {
+            boid_speed: 100.,
+            boid_count: 3,
+            boid_color: Color::from_rgba(0, 255, 0, 255),
+            boid_radius: 2.,
+            emission_delay_ms: 100,
+            diffusion: 0.,
+            flight_pattern: vec![Point(100., 100.), Point(200., 100.)],
+            duration: Duration::from_secs(10),
+            perception_radius: 50.,
+            separation: 1.,
+            alignment: 1.,
+            cohesion: 1.,
+        };
+
+        let rng = Rc::new(Rng::new(1));
+        SwarmEmitter::new(
+            Position::new(0., 0.),
+            options,
+            Duration::ZERO,
+            rng,
+            Rc::new(SemiImplicitEuler),
+        )
+    }
+
+    #[test]
+    fn spawns_with_delay() {
+        let mut swarm = swarm();
+
+        swarm.step(Duration::ZERO, 0.);
+        assert_eq!(1, swarm.boids.len());
+
+        swarm.step(Duration::from_millis(250), 0.25);
+        assert_eq!(3, swarm.boids.len());
+
+        swarm.step(Duration::from_millis(1000), 0.75);
+        assert_eq!(3, swarm.boids.len());
+    }
+
+    #[test]
+    fn follows_leader() {
+        let mut swarm = swarm();
+
+        for ms in 0..=600 {
+            swarm.step(Duration::from_millis(ms * 2), 0.002);
+        }
+
+        // The leader reached the second point and heads back to the first one.
+        let leader = swarm.boids[0];
+        assert!(leader.vx < 0.);
+
+        for boid in swarm.boids.iter().skip(1) {
+            assert!(120. < boid.x);
+        }
+    }
+
+    #[test]
+    fn separates_from_close_boids_only() {
+        let mut swarm = swarm();
+        swarm.alignment = 0.;
+        swarm.cohesion = 0.;
+
+        let still = |x, y| Boid {
+            x,
+            y,
+            vx: 0.,
+            vy: 0.,
+        };
+
+        // The leader is out of range, the close boid on the left pushes to the right.
+        let boids = [still(0., 0.), still(300., 0.), still(290., 0.)];
+        let (ax, ay) = swarm.steering(&boids, 1);
+        assert_eq!((100., 0.), (ax, ay));
+
+        // Only the far leader, nothing to steer away from.
+        let (ax, ay) = swarm.steering(&boids[..2], 1);
+        assert_eq!((0., 0.), (ax, ay));
+    }
 }
@@ -1,3 +1,5 @@
+use crate::position::Position;
+use crate::renderer::Renderer;
 use macroquad::prelude::*;
 use std::collections::VecDeque;
 
@@ -41,8 +43,8 @@ pub struct TrailOptions {
 }
 
 pub struct TrailData {
-    pub x_abs: f32,
-    pub y_abs: f32,
+    pub x: f32,
+    pub y: f32,
     pub color: Color,
     pub radius: f32,
 }
@@ -76,27 +78,19 @@ impl TrailAnimation {
         let is_in_cycle = self.from_ms <= cycle_ms && cycle_ms < self.until_ms;
         let is_new_iteration = self.iteration != new_iteration;
 
-        self.trail.iter_mut().reduce(|from, to| {
-            if is_new_iteration {
-                from.color.a -= self.opacity_loss_per_update;
-            }
-
-            if !from.line_end {
-                draw_line(from.x, from.y, to.x, to.y, to.diameter, to.color);
+        if is_new_iteration {
+            for point in self.trail.iter_mut().rev().skip(1) {
+                point.color.a -= self.opacity_loss_per_update;
             }
 
-            to
-        });
-
-        if is_new_iteration {
             self.iteration = new_iteration;
             self.trail.retain(|point| point.color.a.is_sign_positive());
         }
 
         if is_in_cycle {
             let diameter = data.radius * 2. * self.diameter_fraction;
-            let x = data.x_abs;
-            let y = data.y_abs;
+            let x = data.x;
+            let y = data.y;
 
             let create_new_point = || TrailPoint {
                 color: data.color,
@@ -121,4 +115,24 @@ impl TrailAnimation {
             last.line_end = true;
         }
     }
+
+    pub fn draw(&self, renderer: &mut dyn Renderer, grid_position: &Position) {
+        let x = grid_position.x;
+        let y = grid_position.y;
+
+        self.trail.iter().reduce(|from, to| {
+            if !from.line_end {
+                renderer.draw_line(
+                    from.x + x,
+                    from.y + y,
+                    to.x + x,
+                    to.y + y,
+                    to.diameter,
+                    to.color,
+                );
+            }
+
+            to
+        });
+    }
 }
@@ -1,5 +1,7 @@
 use super::trail_animation::TrailAnimation;
 use super::trail_animation::TrailData;
+use crate::position::Position;
+use crate::renderer::Renderer;
 
 #[derive(Debug, Clone)]
 pub struct TrailHandler {
@@ -14,4 +16,10 @@ impl TrailHandler {
             animation.animate(data, cycle_ms);
         }
     }
+
+    pub fn draw(&self, renderer: &mut dyn Renderer, grid_position: &Position) {
+        for animation in self.trail_animations.iter() {
+            animation.draw(renderer, grid_position);
+        }
+    }
 }